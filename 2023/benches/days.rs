@@ -0,0 +1,113 @@
+//! Parse step and both parts of every day, on the resolved puzzle inputs, and
+//! the days that scale worst on larger generated inputs.
+//!
+//! `cargo bench -- --save-baseline <name>` records a baseline and
+//! `cargo bench -- --baseline <name>` compares against it.
+
+use std::hint::black_box;
+
+use aoc_2023::{
+    day1, day10, day11, day12, day2, day3, day4, day5, day6, day7, day8, day9,
+    gen::{self, Knobs},
+    solution::Solution,
+    CALENDAR,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn input(day: u8) -> String {
+    CALENDAR
+        .inputs()
+        .stdin(false)
+        .load(day)
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn bench_day<S: Solution>(c: &mut Criterion) {
+    let input = input(S::DAY);
+    let parsed = S::parse(&input).unwrap();
+    let mut group = c.benchmark_group(format!("day{}", S::DAY));
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(&input))));
+    group.bench_function("part1", |b| b.iter(|| S::part1(black_box(&parsed))));
+    group.bench_function("part2", |b| b.iter(|| S::part2(black_box(&parsed))));
+    group.finish();
+}
+
+fn days(c: &mut Criterion) {
+    bench_day::<day1::Day1>(c);
+    bench_day::<day2::Day2>(c);
+    bench_day::<day3::Day3>(c);
+    bench_day::<day4::Day4>(c);
+    bench_day::<day5::Day5>(c);
+    bench_day::<day6::Day6>(c);
+    bench_day::<day7::Day7>(c);
+    bench_day::<day8::Day8>(c);
+    bench_day::<day9::Day9>(c);
+    bench_day::<day10::Day10>(c);
+    bench_day::<day11::Day11>(c);
+    bench_day::<day12::Day12>(c);
+}
+
+fn refactors(c: &mut Criterion) {
+    let input1 = input(1);
+    let mut group = c.benchmark_group("day1");
+    group.bench_function("refactor/part1", |b| {
+        b.iter(|| day1::refactor::day1_part1(black_box(input1.as_bytes())))
+    });
+    group.bench_function("refactor/part2", |b| {
+        b.iter(|| day1::refactor::day1_part2(black_box(input1.as_bytes())))
+    });
+    group.finish();
+
+    let input2 = input(2);
+    let mut group = c.benchmark_group("day2");
+    group.bench_function("refactor/part1", |b| {
+        b.iter(|| day2::refactor::day2_part1(black_box(input2.as_bytes()), 12, 14, 13))
+    });
+    group.bench_function("refactor/part2", |b| {
+        b.iter(|| day2::refactor::day2_part2(black_box(input2.as_bytes())))
+    });
+    group.finish();
+}
+
+/// Both parts on generated inputs of the real size and larger, for the days
+/// that scale worst.
+fn generated(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generated");
+    group.sample_size(10);
+    let day11 = Knobs::of(11);
+    let day12 = Knobs::of(12);
+    for (day, name, knobs) in [
+        (11, "galaxies x1", day11),
+        (
+            11,
+            "galaxies x2",
+            Knobs {
+                lines: day11.lines * 2,
+                width: day11.width * 2,
+                height: day11.height * 2,
+                ..day11
+            },
+        ),
+        (12, "springs x1", day12),
+        (
+            12,
+            "springs x2",
+            Knobs {
+                springs: day12.springs * 2,
+                ..day12
+            },
+        ),
+    ] {
+        let input = gen::generate(day, 0, &knobs).unwrap();
+        let solver = aoc_2023::get(day).unwrap();
+        for part in [1, 2] {
+            group.bench_function(format!("day{day}/{name}/part{part}"), |b| {
+                b.iter(|| solver.solve(black_box(&input), part))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, days, refactors, generated);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+#![no_main]
+
+use aoc_2023::day10;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    // the walks follow pipes off the map and into ones that do not connect
+    let _ = day10::day10_part1(&input);
+    let _ = day10::day10_part2(&input);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use aoc_2023::day12;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    // the arrangements are counted too, they index the row by the group sizes
+    let _ = day12::day12_part1(input.as_bytes());
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use aoc_2023::day1;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // the spelled digits overlap, part 2 matches them on every byte offset
+    let _ = day1::day1_part1(data);
+    let _ = day1::day1_part2(data);
+});
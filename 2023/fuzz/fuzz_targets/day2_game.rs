@@ -0,0 +1,9 @@
+#![no_main]
+
+use aoc_2023::day2::refactor;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    let _ = refactor::parse(&input);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use aoc_2023::day3;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    // both sums look around every number, on the edges of ragged rows too
+    let _ = day3::day3(&input);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use aoc_2023::day5;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    // the ranges of a valid almanac still overflow while they are mapped
+    if let Ok(almanac) = day5::parse(&input) {
+        let _ = day5::lowest_location(&almanac);
+        let _ = day5::lowest_range_location(&almanac);
+    }
+});
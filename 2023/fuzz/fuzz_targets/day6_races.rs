@@ -0,0 +1,11 @@
+#![no_main]
+
+use aoc_2023::day6;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    // the kerned numbers of part 2 reach the overflow checks of the quadratic
+    let _ = day6::day6_part1(input.to_string());
+    let _ = day6::day6_part2(input.into_owned());
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use aoc_2023::day7;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = day7::day7_part1(data);
+    let _ = day7::day7_part2(data);
+});
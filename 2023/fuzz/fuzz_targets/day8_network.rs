@@ -0,0 +1,9 @@
+#![no_main]
+
+use aoc_2023::day8;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    let _ = day8::parse_input(&input);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use aoc_2023::day9;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // the differences of extreme values overflow
+    let _ = day9::day9_part1(data);
+    let _ = day9::day9_part2(data);
+});
@@ -1,46 +1,51 @@
-use anyhow::{Ok, Result};
-use std::collections::HashMap;
-
-fn get_calibration(input: String, numbers: HashMap<&str, u32>) -> u32 {
-    let lines: Vec<&str> = input.split("\n").collect();
-    return lines
-        .iter()
-        .map(|line| {
-            let mut first_idx = line.len();
-            let mut first_digit: &str = "";
-            let mut last_idx = 0;
-            let mut last_digit: &str = "";
-            numbers.keys().for_each(|n| {
-                let result = line.find(n).unwrap_or(line.len());
-                if result < first_idx {
-                    first_idx = result;
-                    first_digit = &n;
-                }
-                let result = line.rfind(n).unwrap_or(0);
-                if result > last_idx {
-                    last_idx = result;
-                    last_digit = &n;
-                }
-            });
+//! Day 1: Trebuchet?!
 
-            if last_idx == 0 {
-                last_digit = first_digit;
-            }
+use std::{collections::HashMap, io::BufRead};
 
-            let zero = 0;
-            return format!(
-                "{}{}",
-                numbers.get(first_digit).unwrap_or(&zero),
-                numbers.get(last_digit).unwrap_or(&zero)
-            )
-            .parse::<u32>()
-            .expect("wrong format");
-        })
-        .sum();
+use anyhow::bail;
+use tracing::trace;
+
+use crate::{
+    error::{InputError, Line},
+    solution::{Answer, Implementation, Solution},
+};
+
+/// The first and last digit of `line` as a number.
+fn calibration(line: &str, numbers: &HashMap<&str, u64>) -> u64 {
+    let mut first_idx = line.len();
+    let mut first_digit: &str = "";
+    let mut last_idx = 0;
+    let mut last_digit: &str = "";
+    numbers.keys().for_each(|n| {
+        let result = line.find(n).unwrap_or(line.len());
+        if result < first_idx {
+            first_idx = result;
+            first_digit = n;
+        }
+        let result = line.rfind(n).unwrap_or(0);
+        if result > last_idx {
+            last_idx = result;
+            last_digit = n;
+        }
+    });
+
+    if last_idx == 0 {
+        last_digit = first_digit;
+    }
+
+    let zero = 0;
+    format!(
+        "{}{}",
+        numbers.get(first_digit).unwrap_or(&zero),
+        numbers.get(last_digit).unwrap_or(&zero)
+    )
+    .parse::<u64>()
+    .expect("wrong format")
 }
 
-fn day1_part1(input: String) -> u32 {
-    let numbers: HashMap<&str, u32> = HashMap::from([
+/// The digits, and with `spelled` the digits spelled out, by their text.
+fn digits(spelled: bool) -> HashMap<&'static str, u64> {
+    let mut numbers = HashMap::from([
         ("1", 1),
         ("2", 2),
         ("3", 3),
@@ -51,34 +56,97 @@ fn day1_part1(input: String) -> u32 {
         ("8", 8),
         ("9", 9),
     ]);
-    return get_calibration(input, numbers);
+    if spelled {
+        numbers.extend([
+            ("one", 1),
+            ("two", 2),
+            ("three", 3),
+            ("four", 4),
+            ("five", 5),
+            ("six", 6),
+            ("seven", 7),
+            ("eight", 8),
+            ("nine", 9),
+        ]);
+    }
+    numbers
 }
 
-fn day1_part2(input: String) -> u32 {
-    let numbers: HashMap<&str, u32> = HashMap::from([
-        ("one", 1),
-        ("two", 2),
-        ("three", 3),
-        ("four", 4),
-        ("five", 5),
-        ("six", 6),
-        ("seven", 7),
-        ("eight", 8),
-        ("nine", 9),
-        ("1", 1),
-        ("2", 2),
-        ("3", 3),
-        ("4", 4),
-        ("5", 5),
-        ("6", 6),
-        ("7", 7),
-        ("8", 8),
-        ("9", 9),
-    ]);
-    return get_calibration(input, numbers);
+fn get_calibration(input: impl BufRead, numbers: HashMap<&str, u64>) -> Result<u64, InputError> {
+    Line::sum_each(1, input, |line| {
+        let value = calibration(line.text, &numbers);
+        trace!(line = line.number, value, "calibration");
+        Ok(value)
+    })
+}
+
+/// Sum of the first and last digit of every line.
+pub fn day1_part1(input: impl BufRead) -> Result<u64, InputError> {
+    get_calibration(input, digits(false))
+}
+
+/// Like [`day1_part1`] but digits can also be spelled out: `one`, `two`...
+pub fn day1_part2(input: impl BufRead) -> Result<u64, InputError> {
+    get_calibration(input, digits(true))
 }
 
-mod refactor {
+/// [`day1_part1`], or [`day1_part2`] with `spelled`, on the lines of the
+/// document.
+pub fn calibrations(lines: &[String], spelled: bool) -> u64 {
+    let numbers = digits(spelled);
+    (1..)
+        .zip(lines)
+        .map(|(line, text)| {
+            let value = calibration(text, &numbers);
+            trace!(line, value, "calibration");
+            value
+        })
+        .sum()
+}
+
+/// [`Solution`] for day 1.
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    /// The lines of the calibration document.
+    type Input = Vec<String>;
+    const IMPLEMENTATIONS: &'static [Implementation] = &[
+        Implementation {
+            name: "refactor",
+            part: 1,
+            solve: |input| Ok(refactor::day1_part1(input.as_bytes())?.into()),
+        },
+        Implementation {
+            name: "refactor",
+            part: 2,
+            solve: |input| Ok(refactor::day1_part2(input.as_bytes())?.into()),
+        },
+    ];
+
+    fn parse(input: &str) -> anyhow::Result<Vec<String>> {
+        Ok(input.lines().map(str::to_owned).collect())
+    }
+
+    fn part1(lines: &Vec<String>) -> anyhow::Result<Answer> {
+        Ok(calibrations(lines, false).into())
+    }
+
+    fn part2(lines: &Vec<String>) -> anyhow::Result<Answer> {
+        Ok(calibrations(lines, true).into())
+    }
+
+    fn solve_reader(reader: &mut dyn BufRead, part: u8) -> anyhow::Result<Answer> {
+        match part {
+            1 => Ok(day1_part1(reader)?.into()),
+            2 => Ok(day1_part2(reader)?.into()),
+            _ => bail!("part {part} does not exist"),
+        }
+    }
+}
+
+/// Day 1 solved matching slices against the digits.
+pub mod refactor {
     //refactor using slices and patter maching
     fn get_first_number(input: &str, part2: bool) -> char {
         match input {
@@ -101,7 +169,7 @@ mod refactor {
             _ if part2 && input.starts_with("nine") => '9',
             _ if input.starts_with("9") => '9',
             _ => {
-                if input.len() < 1 {
+                if input.is_empty() {
                     return '0';
                 }
                 get_first_number(&input[1..], part2)
@@ -130,7 +198,7 @@ mod refactor {
             _ if part2 && input.ends_with("nine") => '9',
             _ if input.ends_with("9") => '9',
             _ => {
-                if input.len() < 1 {
+                if input.is_empty() {
                     return '0';
                 }
                 get_last_number(&input[0..input.len() - 1], part2)
@@ -138,79 +206,130 @@ mod refactor {
         }
     }
 
-    fn run(input: String, part2: bool) -> u32 {
-        let lines: Vec<&str> = input.split("\n").collect();
-        lines
-            .iter()
-            .map(|line| {
-                let first_digit = get_first_number(line, part2);
-                let mut last_digit = get_last_number(line, part2);
-                if last_digit == '0' {
-                    last_digit = first_digit;
-                }
-                return format!("{}{}", first_digit, last_digit)
-                    .parse::<u32>()
-                    .expect("wrong format");
-            })
-            .sum()
+    use std::io::BufRead;
+
+    use crate::error::{InputError, Line};
+
+    fn run(input: impl BufRead, part2: bool) -> Result<u64, InputError> {
+        Line::sum_each(1, input, |line| {
+            let first_digit = get_first_number(line.text, part2);
+            let mut last_digit = get_last_number(line.text, part2);
+            if last_digit == '0' {
+                last_digit = first_digit;
+            }
+            Ok(format!("{}{}", first_digit, last_digit)
+                .parse::<u64>()
+                .expect("wrong format"))
+        })
     }
 
-    pub fn day1_part1(input: String) -> u32 {
+    /// Same as [`super::day1_part1`].
+    pub fn day1_part1(input: impl BufRead) -> Result<u64, InputError> {
         run(input, false)
     }
 
-    pub fn day1_part2(input: String) -> u32 {
+    /// Same as [`super::day1_part2`].
+    pub fn day1_part2(input: impl BufRead) -> Result<u64, InputError> {
         run(input, true)
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
-    // uncomment to test the refactor with slices
-    // use super::refactor::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-    #[test]
-    fn day1_part1_small_test() {
-        let test = String::from(
-            r"1abc2
-pqr3stu8vwx
-a1b2c3d4e5f
-treb7uchet",
-        );
-        assert_eq!(day1_part1(test), 142);
-    }
-
+    use insta::assert_debug_snapshot;
+    use proptest::prelude::*;
     #[test]
     fn day1_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day1/input.txt")?;
+        let input = test_input(1)?;
 
-        let r = day1_part1(input);
+        let r = day1_part1(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 54331);
         Ok(())
     }
 
-    #[test]
-    fn day1_part2_small_test() {
-        let test = String::from(
-            r"two1nine
-eightwothree
-abcone2threexyz
-xtwone3four
-4nineeightseven2
-zoneight234
-7pqrstsixteen",
-        );
-        assert_eq!(day1_part2(test), 281);
-    }
-
     #[test]
     fn day1_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day1/input.txt")?;
-        let r = day1_part2(input);
+        let input = test_input(1)?;
+        let r = day1_part2(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 54518);
         Ok(())
     }
+
+    #[test]
+    fn day1_edge_cases() -> Result<()> {
+        for (line, part1, part2) in [
+            ("eightwo", 0, 82),
+            ("oneight", 0, 18),
+            ("7", 77, 77),
+            ("xtwoy", 0, 22),
+            ("abc", 0, 0),
+            ("", 0, 0),
+            ("1eightwo1", 11, 11),
+        ] {
+            assert_eq!(day1_part1(line.as_bytes())?, part1, "{line}");
+            assert_eq!(refactor::day1_part1(line.as_bytes())?, part1, "{line}");
+            assert_eq!(day1_part2(line.as_bytes())?, part2, "{line}");
+            assert_eq!(refactor::day1_part2(line.as_bytes())?, part2, "{line}");
+        }
+        Ok(())
+    }
+
+    /// Lines of digits, spelled digits, overlapping spellings and filler.
+    fn lines() -> impl Strategy<Value = String> {
+        let token = prop::sample::select(vec![
+            "1",
+            "2",
+            "5",
+            "9",
+            "one",
+            "two",
+            "three",
+            "six",
+            "seven",
+            "eight",
+            "nine",
+            "eightwo",
+            "oneight",
+            "twone",
+            "sevenine",
+            "threeight",
+            "a",
+            "x",
+            "on",
+            "tw",
+            "nin",
+        ]);
+        let line = prop::collection::vec(token, 0..8).prop_map(|tokens| tokens.concat());
+        prop::collection::vec(line, 1..10).prop_map(|lines| lines.join("\n"))
+    }
+
+    proptest! {
+        #[test]
+        fn day1_refactor_agrees(input in lines()) {
+            let input = input.as_bytes();
+            prop_assert_eq!(day1_part1(input).unwrap(), refactor::day1_part1(input).unwrap());
+            prop_assert_eq!(day1_part2(input).unwrap(), refactor::day1_part2(input).unwrap());
+        }
+    }
+
+    #[test]
+    fn day1_snapshot() -> Result<()> {
+        for (name, example, spelled) in [
+            ("calibrations", "example1", false),
+            ("spelled_calibrations", "example2", true),
+        ] {
+            let numbers = digits(spelled);
+            let values = fixture(1, example)?
+                .lines()
+                .map(|line| (line.to_owned(), calibration(line, &numbers)))
+                .collect::<Vec<(String, u64)>>();
+            assert_debug_snapshot!(name, values);
+        }
+        Ok(())
+    }
 }
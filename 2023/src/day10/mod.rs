@@ -1,24 +1,26 @@
+//! Day 10: Pipe Maze.
+
 use std::collections::{BTreeMap, BTreeSet};
 
-use itertools::Itertools;
+use tracing::debug;
+
+use crate::{
+    error::ParseError,
+    grid::{Grid, Pos},
+    solution::{Answer, Solution},
+};
+
+/// Every tile of the map.
+const TILES: &str = "|-LJ7F.S";
+
 #[derive(Debug, Eq, PartialEq)]
 enum Status {
     In,
     Out,
 }
 
-fn get_value(map: &Vec<Vec<char>>, x: i64, y: i64) -> &char {
-    if x < 0 || y < 0 {
-        return &'.';
-    }
-    if let Some(row) = map.get(x as usize) {
-        return row.get(y as usize).unwrap_or(&'.');
-    }
-    return &'.';
-}
-
-fn walk(map: &Vec<Vec<char>>, current: (i64, i64), path: &mut BTreeSet<(i64, i64)>) {
-    let directions: BTreeMap<char, [(i64, i64); 2]> = BTreeMap::from([
+fn walk(map: &Grid<char>, current: Option<Pos>, path: &mut BTreeSet<Pos>) {
+    let directions: BTreeMap<char, [(isize, isize); 2]> = BTreeMap::from([
         ('|', [(-1, 0), (1, 0)]),
         ('-', [(0, -1), (0, 1)]),
         ('7', [(0, -1), (1, 0)]),
@@ -26,84 +28,129 @@ fn walk(map: &Vec<Vec<char>>, current: (i64, i64), path: &mut BTreeSet<(i64, i64
         ('L', [(0, 1), (-1, 0)]),
         ('F', [(0, 1), (1, 0)]),
     ]);
-    let mut next = current;
+    let Some(mut next) = current else {
+        return;
+    };
     loop {
-        let current_value = get_value(map, next.0, next.1);
-        if path.contains(&next) || *current_value == '.' {
+        if path.contains(&next) {
             return;
         }
-        let current_direction = directions.get(current_value).unwrap();
+        // a `.` or another `S` ends the walk
+        let Some(current_direction) = directions.get(&map[next]) else {
+            return;
+        };
         path.insert(next);
-        if let Some(next_dir) = current_direction
-            .iter()
-            .find(|(x, y)| !path.contains(&(next.0 + x, next.1 + y)))
-        {
-            next = (next.0 + next_dir.0, next.1 + next_dir.1);
+        // leaving the map ends the walk like a `.` tile would
+        if let Some(next_dir) = current_direction.iter().find(|dir| {
+            map.offset(next, **dir)
+                .is_none_or(|pos| !path.contains(&pos))
+        }) {
+            match map.offset(next, *next_dir) {
+                Some(pos) => next = pos,
+                None => return,
+            }
         }
     }
 }
 
-fn day10_part1(input: String) -> usize {
-    let map = input
-        .lines()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
-    let mut path1: BTreeSet<(i64, i64)> = BTreeSet::new();
-    let mut path2: BTreeSet<(i64, i64)> = BTreeSet::new();
-    let mut path3: BTreeSet<(i64, i64)> = BTreeSet::new();
-    let mut path4: BTreeSet<(i64, i64)> = BTreeSet::new();
-
-    let mut start = (0, 0);
-    'main: for (i, row) in map.iter().enumerate() {
-        for (j, value) in row.iter().enumerate() {
-            if *value == 'S' {
-                start = (i as i64, j as i64);
-                break 'main;
-            }
-        }
-    }
-    path1.insert(start);
-    path2.insert(start);
-    path3.insert(start);
-    path4.insert(start);
-    walk(&map, (start.0 + 1, start.1), &mut path1);
-    walk(&map, (start.0, start.1 + 1), &mut path2);
-    walk(&map, (start.0, start.1 - 1), &mut path3);
-    walk(&map, (start.0 - 1, start.1), &mut path4);
-    let paths = [path1, path2, path3, path4];
-    paths.iter().map(|p| p.len()).sorted().last().unwrap() / 2
+/// The map of pipes and its start tile, only built by [`parse`] so that
+/// there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Maze {
+    map: Grid<char>,
+    start: Pos,
 }
-fn day10_part2(input: String) -> usize {
-    let map = input
-        .lines()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
-    let mut path1: BTreeSet<(i64, i64)> = BTreeSet::new();
-    let mut path2: BTreeSet<(i64, i64)> = BTreeSet::new();
-    let mut path3: BTreeSet<(i64, i64)> = BTreeSet::new();
-    let mut path4: BTreeSet<(i64, i64)> = BTreeSet::new();
-
-    let mut start = (0, 0);
-    'main: for (i, row) in map.iter().enumerate() {
-        for (j, value) in row.iter().enumerate() {
-            if *value == 'S' {
-                start = (i as i64, j as i64);
-                break 'main;
-            }
-        }
+
+impl Maze {
+    /// The tiles.
+    pub fn map(&self) -> &Grid<char> {
+        &self.map
+    }
+
+    /// The first start tile in reading order.
+    pub fn start(&self) -> Pos {
+        self.start
     }
-    path1.insert(start);
-    path2.insert(start);
-    path3.insert(start);
-    path4.insert(start);
-    walk(&map, (start.0 + 1, start.1), &mut path1);
-    walk(&map, (start.0, start.1 + 1), &mut path2);
-    walk(&map, (start.0, start.1 - 1), &mut path3);
-    walk(&map, (start.0 - 1, start.1), &mut path4);
-    let paths = [path1, path2, path3, path4];
-    paths.iter().sorted_by(|a, b| a.len().cmp(&b.len()));
-    let pipes = paths.first().unwrap();
-    map.iter()
+}
+
+/// The walks from the 4 tiles around the start: down, right, left and up.
+fn paths(Maze { map, start }: &Maze) -> [BTreeSet<Pos>; 4] {
+    let start = *start;
+    [(1, 0), (0, 1), (0, -1), (-1, 0)].map(|delta| {
+        let mut path = BTreeSet::from([start]);
+        walk(map, map.offset(start, delta), &mut path);
+        debug!(?start, ?delta, length = path.len(), "walk");
+        path
+    })
+}
+
+/// Parses the map of pipes, it must have a start tile.
+pub fn parse(input: &str) -> Result<Maze, ParseError> {
+    let map = Grid::parse_with(10, input, &format!("a tile in `{TILES}`"), |c| {
+        TILES.contains(c).then_some(c)
+    })?;
+    let Some(start) = map.position(|tile| *tile == 'S') else {
+        let first = input.lines().next().unwrap_or_default();
+        return Err(ParseError::new(
+            10,
+            1,
+            1,
+            first,
+            "expected a start tile `S`",
+        ));
+    };
+    Ok(Maze { map, start })
+}
+
+/// Checks that the map has a single start tile.
+pub fn validate(input: &str) -> Result<Vec<ParseError>, ParseError> {
+    let maze = parse(input)?;
+    let (row, column) = maze.start;
+    Ok(maze
+        .map
+        .iter()
+        .filter(|(pos, tile)| **tile == 'S' && *pos != maze.start)
+        .map(|((other_row, other_column), _)| {
+            let message = format!(
+                "expected a single start tile, the first one is at line {} column {}",
+                row + 1,
+                column + 1
+            );
+            ParseError::new(10, other_row + 1, other_column + 1, "S", message)
+        })
+        .collect())
+}
+
+/// Steps to the farthest point of the loop.
+pub fn day10_part1(input: &str) -> Result<usize, ParseError> {
+    Ok(farthest(&parse(input)?))
+}
+
+/// Tiles enclosed by the loop.
+pub fn day10_part2(input: &str) -> Result<usize, ParseError> {
+    Ok(enclosed(&parse(input)?))
+}
+
+/// The loop through the start tile: the longest of the walks from it.
+fn main_loop(maze: &Maze) -> BTreeSet<Pos> {
+    let pipes = paths(maze)
+        .into_iter()
+        .max_by_key(|path| path.len())
+        .expect("there are 4 walks");
+    debug!(length = pipes.len(), "chosen loop");
+    pipes
+}
+
+/// [`day10_part1`] on the parsed map.
+pub fn farthest(maze: &Maze) -> usize {
+    main_loop(maze).len() / 2
+}
+
+/// [`day10_part2`] on the parsed map.
+pub fn enclosed(maze: &Maze) -> usize {
+    let pipes = main_loop(maze);
+    maze.map
+        .rows()
         .enumerate()
         .map(|(x, line)| {
             let mut status = Status::Out;
@@ -111,7 +158,7 @@ fn day10_part2(input: String) -> usize {
             line.iter()
                 .enumerate()
                 .filter(|(y, l)| {
-                    if pipes.contains(&(x as i64, *y as i64)) {
+                    if pipes.contains(&(x, *y)) {
                         if ['S', '|', '7', 'F'].contains(l) {
                             status = match status {
                                 Status::In => Status::Out,
@@ -131,101 +178,92 @@ fn day10_part2(input: String) -> usize {
         .sum()
 }
 
+/// [`Solution`] for day 10.
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input = Maze;
+
+    fn parse(input: &str) -> anyhow::Result<Maze> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(maze: &Maze) -> anyhow::Result<Answer> {
+        Ok(farthest(maze).into())
+    }
+
+    fn part2(maze: &Maze) -> anyhow::Result<Answer> {
+        Ok(enclosed(maze).into())
+    }
+
+    fn validate(input: &str) -> anyhow::Result<Vec<ParseError>> {
+        Ok(validate(input)?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
+    use insta::assert_debug_snapshot;
     #[test]
-    fn day10_part1_small_test() {
-        let test = String::from(
-            r".....
-.S-7.
-.|.|.
-.L-J.
-.....",
-        );
-        assert_eq!(day10_part1(test), 4);
-    }
+    fn day10_part1_test() -> Result<()> {
+        let input = test_input(10)?;
 
-    #[test]
-    fn day10_part1_small_test_2() {
-        let test = String::from(
-            r"..F7.
-.FJ|.
-SJ.L7
-|F--J
-LJ...",
-        );
-        assert_eq!(day10_part1(test), 8);
+        let r = day10_part1(&input)?;
+        println!("{}", r);
+        assert_eq!(r, 6820);
+        Ok(())
     }
 
     #[test]
-    fn day10_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day10/input.txt")?;
-
-        let r = day10_part1(input);
+    fn day10_part2_test() -> Result<()> {
+        let input = test_input(10)?;
+        let r = day10_part2(&input)?;
         println!("{}", r);
-        assert_eq!(r, 6820);
+        assert_eq!(r, 337);
         Ok(())
     }
 
     #[test]
-    fn day10_part2_small_test() {
-        let test = String::from(
-            r"...........
-.S-------7.
-.|F-----7|.
-.||.....||.
-.||.....||.
-.|L-7.F-J|.
-.|..|.|..|.
-.L--J.L--J.
-...........",
-        );
-        assert_eq!(day10_part2(test), 4);
+    fn day10_second_start() -> Result<()> {
+        assert_eq!(day10_part1(".....\n.S-S.\n.....")?, 1);
+        Ok(())
     }
 
     #[test]
-    fn day10_part2_small_test2() {
-        let test = String::from(
-            r".F----7F7F7F7F-7....
-.|F--7||||||||FJ....
-.||.FJ||||||||L7....
-FJL7L7LJLJ||LJ.L-7..
-L--J.L7...LJS7F-7L7.
-....F-J..F7FJ|L7L7L7
-....L7.F7||L7|.L7L7|
-.....|FJLJ|FJ|F7|.LJ
-....FJL-7.||.||||...
-....L---J.LJ.LJLJ...",
-        );
-        assert_eq!(day10_part2(test), 8);
+    fn day10_chosen_loop() -> Result<()> {
+        // the walk down from `S` is a dead end, the loop goes up and right
+        let map = "....\n.F-7\n.|.|\n.L-S\n...|";
+        assert_eq!(day10_part1(map)?, 4);
+        assert_eq!(day10_part2(map)?, 1);
+        Ok(())
     }
 
     #[test]
-    fn day10_part2_small_test3() {
-        let test = String::from(
-            r"FF7FSF7F7F7F7F7F---7
-L|LJ||||||||||||F--J
-FL-7LJLJ||||||LJL-77
-F--JF--7||LJLJ7F7FJ-
-L---JF-JLJ.||-FJLJJ7
-|F|F-JF---7F7-L7L|7|
-|FFJF7L7F-JF7|JL---7
-7-L-JL7||F7|L7F-7F7|
-L.L7LFJ|||||FJL7||LJ
-L7JLJL-JLJLJL--JLJ.L",
+    fn day10_validate_test() -> Result<()> {
+        assert_eq!(validate(&test_input(10)?)?, vec![]);
+        let violations = validate(".S-7.\n.|.|.\n.L-JS")?;
+        assert_eq!(
+            violations,
+            vec![ParseError::new(
+                10,
+                3,
+                5,
+                "S",
+                "expected a single start tile, the first one is at line 1 column 2"
+            )]
         );
-        assert_eq!(day10_part2(test), 10);
+        Ok(())
     }
 
     #[test]
-    fn day10_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day10/input.txt")?;
-        let r = day10_part2(input);
-        println!("{}", r);
-        assert_eq!(r, 337);
+    fn day10_snapshot() -> Result<()> {
+        let maze = parse(&fixture(10, "example1")?)?;
+        assert_eq!(maze.start(), (1, 1));
+        assert_debug_snapshot!("walks", paths(&maze));
         Ok(())
     }
 }
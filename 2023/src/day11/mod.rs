@@ -1,140 +1,205 @@
+//! Day 11: Cosmic Expansion.
+
+use anyhow::{Context, Result};
 use itertools::Itertools;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-fn day11(input: String, factor: usize) -> usize {
-    let mut map = vec![];
-    let mut empty_rows = vec![];
-    let mut empty_cols = vec![];
-    input.lines().enumerate().for_each(|(i, line)| {
-        if !line.contains("#") {
-            empty_rows.push(i);
-        }
-        map.push(line.chars().collect::<Vec<char>>());
-    });
-    'main: for (j, char) in map
-        .first()
-        .expect("Wrong map input")
-        .clone()
+use tracing::debug;
+
+use crate::{
+    error::{Line, ParseError},
+    grid::{Grid, Pos},
+    solution::{Answer, Solution},
+};
+
+/// Parses the image of the galaxies, it must not be empty.
+pub fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    let map = Grid::parse_with(11, input, "`.` or `#`", |c| {
+        (c == '.' || c == '#').then_some(c)
+    })?;
+    if map.height() == 0 {
+        return Err(ParseError::truncated(11, 1, "expected a map"));
+    }
+    Ok(map)
+}
+
+/// Sum of the distances between every pair of galaxies,
+/// every empty row and column grows by `factor`.
+pub fn day11(input: &str, factor: usize) -> Result<usize> {
+    distances(&parse(input)?, factor)
+}
+
+/// [`day11`] on the parsed image, fails when the expanded image or the sum
+/// does not fit in `usize`.
+pub fn distances(map: &Grid<char>, factor: usize) -> Result<usize> {
+    let galaxies = expanded_galaxies(map, factor)
+        .with_context(|| format!("the image grown by {factor} overflows usize"))?;
+    // the distance is the sum of the distances along each axis
+    let rows = galaxies.iter().map(|pos| pos.0).collect::<Vec<usize>>();
+    let mut columns = galaxies.iter().map(|pos| pos.1).collect::<Vec<usize>>();
+    columns.sort_unstable();
+    axis_distances(&rows)
+        .zip(axis_distances(&columns))
+        .and_then(|(rows, columns)| rows.checked_add(columns))
+        .context("the sum of the distances overflows usize")
+}
+
+/// The galaxies in reading order, once every empty row and column has grown
+/// by `factor`. `None` when a coordinate does not fit in `usize`.
+fn expanded_galaxies(map: &Grid<char>, factor: usize) -> Option<Vec<Pos>> {
+    let empty_rows = map
+        .rows()
+        .positions(|row| !row.contains(&'#'))
+        .collect::<Vec<usize>>();
+    let empty_cols = map
+        .columns()
+        .positions(|mut column| column.all(|c| *c != '#'))
+        .collect::<Vec<usize>>();
+    let expand = |empties: &[usize], at: usize| {
+        let before = empties.partition_point(|empty| *empty < at);
+        before
+            .checked_mul(factor)
+            .and_then(|grown| (at - before).checked_add(grown))
+    };
+    let galaxies = map
         .iter()
-        .enumerate()
-    {
-        if char == &'#' {
-            continue;
-        }
-        for line in map.iter() {
-            if line.get(j).expect("Wrong map input") == &'#' {
-                continue 'main;
-            }
-        }
-        empty_cols.push(j);
+        .filter(|(_, ch)| **ch == '#')
+        .map(|((row, column), _)| Some((expand(&empty_rows, row)?, expand(&empty_cols, column)?)))
+        .collect::<Option<Vec<Pos>>>()?;
+    debug!(
+        galaxies = galaxies.len(),
+        empty_rows = empty_rows.len(),
+        empty_cols = empty_cols.len(),
+        factor,
+        "expanded"
+    );
+    Some(galaxies)
+}
+
+/// Sum of the distances between every pair of the `sorted` coordinates,
+/// `None` when it does not fit in `usize`.
+fn axis_distances(sorted: &[usize]) -> Option<usize> {
+    let (sum, _) =
+        (0..)
+            .zip(sorted)
+            .try_fold((0usize, 0usize), |(sum, before), (count, coordinate)| {
+                // the coordinates before this one are all smaller
+                let distances = coordinate.checked_mul(count)? - before;
+                Some((
+                    sum.checked_add(distances)?,
+                    before.checked_add(*coordinate)?,
+                ))
+            })?;
+    Some(sum)
+}
+
+/// Checks that every row is as long as the first one, the expansion counts
+/// the empty columns of a rectangular map.
+pub fn validate(input: &str) -> Result<Vec<ParseError>, ParseError> {
+    let mut lines = Line::lines(11, input);
+    let width = lines.next().map_or(0, |first| first.text.chars().count());
+    let violations = lines
+        .filter(|line| line.text.chars().count() != width)
+        .map(|line| line.error(line.text, format!("expected a row of {width} columns")))
+        .collect::<Vec<ParseError>>();
+    if violations.is_empty() {
+        parse(input)?;
     }
-    let mut nums = vec![];
-    map.iter().enumerate().for_each(|(i, row)| {
-        row.iter().enumerate().for_each(|(j, ch)| {
-            if ch == &'#' {
-                nums.push((i, j));
-            }
-        });
-    });
-    let pairs = nums
-        .into_iter()
-        .combinations(2)
-        .map(|c| {
-            let start = *c.first().unwrap();
-            let end = *c.last().unwrap();
-            (start, end)
-        })
-        .collect::<Vec<((usize, usize), (usize, usize))>>();
-
-    pairs
-        .into_par_iter()
-        .fold(
-            || 0,
-            |acc, (start, end)| {
-                manhattan_distance(
-                    (start.0, start.1),
-                    (end.0, end.1),
-                    &empty_rows,
-                    &empty_cols,
-                    factor,
-                ) + acc
-            },
-        )
-        .reduce(|| 0, usize::wrapping_add)
+    Ok(violations)
 }
 
-fn manhattan_distance(
-    start: (usize, usize),
-    end: (usize, usize),
-    empty_rows: &Vec<usize>,
-    empty_cols: &Vec<usize>,
-    grow: usize,
-) -> usize {
-    let mut path_size = 0;
-    for i in start.0.min(end.0)..start.0.max(end.0) {
-        path_size += if empty_rows.contains(&i) { grow } else { 1 };
+/// [`Solution`] for day 11.
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input = Grid<char>;
+
+    fn parse(input: &str) -> Result<Grid<char>> {
+        Ok(parse(input)?)
     }
-    for j in start.1.min(end.1)..start.1.max(end.1) {
-        path_size += if empty_cols.contains(&j) { grow } else { 1 };
+
+    fn part1(map: &Grid<char>) -> Result<Answer> {
+        Ok(distances(map, 2)?.into())
+    }
+
+    fn part2(map: &Grid<char>) -> Result<Answer> {
+        Ok(distances(map, 1000000)?.into())
+    }
+
+    fn validate(input: &str) -> Result<Vec<ParseError>> {
+        Ok(validate(input)?)
     }
-    path_size
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-    #[test]
-    fn day11_part1_small_test() {
-        let test = String::from(
-            r"...#......
-.......#..
-#.........
-..........
-......#...
-.#........
-.........#
-..........
-.......#..
-#...#.....
-",
-        );
-        assert_eq!(day11(test, 2), 374);
-    }
-
+    use insta::assert_debug_snapshot;
     #[test]
     fn day11_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day11/input.txt")?;
+        let input = test_input(11)?;
 
-        let r = day11(input, 2);
+        let r = day11(&input, 2)?;
         println!("{}", r);
         assert_eq!(r, 9734203);
         Ok(())
     }
 
     #[test]
-    fn day11_part2_small_test() {
-        let test = String::from(
-            r"...#......
-.......#..
-#.........
-..........
-......#...
-.#........
-.........#
-..........
-.......#..
-#...#.....
-",
-        );
-        assert_eq!(day11(test, 100), 8410);
+    fn day11_part2_small_test() -> Result<()> {
+        let test = fixture(11, "example")?;
+        assert_eq!(day11(&test, 100)?, 8410);
+        Ok(())
     }
     #[test]
     fn day11_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day11/input.txt")?;
-        let r = day11(input, 1000000);
+        let input = test_input(11)?;
+        let r = day11(&input, 1000000)?;
         println!("{}", r);
         assert_eq!(r, 568914596391);
         Ok(())
     }
+
+    #[test]
+    fn day11_validate_test() -> Result<()> {
+        assert_eq!(validate(&test_input(11)?)?, vec![]);
+        let violations = validate("#..\n..\n.#.\n...#")?;
+        let rows = violations
+            .iter()
+            .map(|v| (v.line, v.message.as_str()))
+            .collect::<Vec<(usize, &str)>>();
+        assert_eq!(
+            rows,
+            [
+                (2, "expected a row of 3 columns"),
+                (4, "expected a row of 3 columns")
+            ]
+        );
+        assert!(validate("#.\n.x").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn day11_overflow() -> Result<()> {
+        let map = parse("#.\n..\n.#")?;
+        assert_eq!(
+            distances(&map, usize::MAX).unwrap_err().to_string(),
+            format!("the image grown by {} overflows usize", usize::MAX)
+        );
+        let map = parse("#..\n...\n..#")?;
+        assert_eq!(
+            distances(&map, usize::MAX / 2).unwrap_err().to_string(),
+            "the sum of the distances overflows usize"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn day11_snapshot() -> Result<()> {
+        let map = parse(&fixture(11, "example")?)?;
+        let galaxies = expanded_galaxies(&map, 2).context("the example overflows")?;
+        assert_debug_snapshot!("galaxies", galaxies);
+        Ok(())
+    }
 }
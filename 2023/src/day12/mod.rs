@@ -1,132 +1,237 @@
-use std::{collections::HashMap, usize};
+//! Day 12: Hot Springs.
 
+use std::{collections::HashMap, io::BufRead};
+
+use anyhow::bail;
+use tracing::debug;
+
+use nom::{
+    bytes::complete::take_while1,
+    character::complete::{char, space1},
+    combinator::{cut, verify},
+    multi::separated_list1,
+    sequence::separated_pair,
+};
+
+use crate::{
+    error::{InputError, Line, ParseError},
+    parsing::{parse_line, unsigned},
+    solution::{Answer, Solution},
+};
+
+/// Arrangements already counted, keyed by the rest of the row and of the
+/// groups, with hit and miss counts for the traces.
+#[derive(Debug, Default)]
+struct Cache {
+    counts: HashMap<(String, Vec<usize>), usize>,
+    hits: usize,
+    misses: usize,
+}
+
+impl Cache {
+    fn get(&mut self, key: &(String, Vec<usize>)) -> Option<usize> {
+        let count = self.counts.get(key).copied();
+        match count {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        count
+    }
+}
+
+/// The arrangements of `pattern` from `index` on with the `valids` groups,
+/// `None` when they do not fit in `usize`.
 fn generate_combinations(
     pattern: &Vec<char>,
     index: usize,
     valids: Vec<usize>,
-    cache: &mut HashMap<(String, Vec<usize>), usize>,
-) -> usize {
+    cache: &mut Cache,
+) -> Option<usize> {
     if index >= pattern.len() {
-        return if valids.is_empty() { 1 } else { 0 };
+        return Some(if valids.is_empty() { 1 } else { 0 });
     }
     if valids.is_empty() {
-        return if pattern[index..].contains(&'#') {
+        return Some(if pattern[index..].contains(&'#') {
             0
         } else {
             1
-        };
+        });
     }
     if let Some(cached) = cache.get(&(pattern[index..].iter().collect::<String>(), valids.clone()))
     {
-        return *cached;
+        return Some(cached);
     }
 
-    let mut sum = 0;
+    let mut sum = 0usize;
     if pattern[index] == '?' || pattern[index] == '.' {
-        sum += generate_combinations(pattern, index + 1, valids.clone(), cache);
+        sum = sum.checked_add(generate_combinations(
+            pattern,
+            index + 1,
+            valids.clone(),
+            cache,
+        )?)?;
     }
 
-    if pattern[index] == '#' || pattern[index] == '?' {
-        if (valids[0] <= (pattern.len() - index))
-            && !pattern[index..index + valids[0]].contains(&'.')
-            && (valids[0] == (pattern.len() - index) || pattern[valids[0] + index] != '#')
-        {
-            sum +=
-                generate_combinations(pattern, index + 1 + valids[0], valids[1..].to_vec(), cache);
-        }
+    if (pattern[index] == '#' || pattern[index] == '?')
+        && (valids[0] <= (pattern.len() - index))
+        && !pattern[index..index + valids[0]].contains(&'.')
+        && (valids[0] == (pattern.len() - index) || pattern[valids[0] + index] != '#')
+    {
+        sum = sum.checked_add(generate_combinations(
+            pattern,
+            index + 1 + valids[0],
+            valids[1..].to_vec(),
+            cache,
+        )?)?;
     }
-    cache.insert(
+    cache.counts.insert(
         (pattern[index..].iter().collect::<String>(), valids.clone()),
         sum,
     );
-    sum
+    Some(sum)
 }
 
-fn day12_part1(input: String) -> usize {
-    let mut cache: HashMap<(String, Vec<usize>), usize> = HashMap::new();
-    input
-        .lines()
+/// A row of springs and the sizes of its damaged groups.
+pub type Record = (String, Vec<usize>);
+
+/// Parses a row of springs and the sizes of its damaged groups.
+fn record(line: Line<'_>) -> Result<(&str, Vec<usize>), ParseError> {
+    parse_line(
+        &line,
+        "springs `?`, `.` or `#` and group sizes like `???.### 1,1,3`",
+        separated_pair(
+            take_while1(|c| "?.#".contains(c)),
+            space1,
+            separated_list1(char(','), cut(verify(unsigned, |size: &usize| *size > 0))),
+        ),
+    )
+}
+
+/// Parses the records, one per line.
+pub fn parse(input: &str) -> Result<Vec<Record>, ParseError> {
+    Line::lines(12, input)
         .map(|line| {
-            let (row, valids) = line.split_once(" ").expect("Wrong input");
-            let valids = valids
-                .split(",")
-                .filter_map(|s| s.parse::<usize>().ok())
-                .collect::<Vec<usize>>();
-            let row = row.chars().collect::<Vec<char>>();
-            generate_combinations(&row, 0, valids, &mut cache)
+            let (row, valids) = record(line)?;
+            Ok((row.to_owned(), valids))
         })
-        .sum()
+        .collect()
 }
 
-fn day12_part2(input: String) -> usize {
-    let mut cache: HashMap<(String, Vec<usize>), usize> = HashMap::new();
-    input
-        .lines()
-        .map(|line| {
-            let (row, valids) = line.split_once(" ").expect("Wrong input");
-            let mut final_row = vec![];
-            let mut final_valid = vec![];
-            for _ in 0..5 {
-                let mut valids = valids
-                    .split(",")
-                    .filter_map(|s| s.parse::<usize>().ok())
-                    .collect::<Vec<usize>>();
-                final_valid.append(&mut valids);
-                let row = row.chars().collect::<Vec<char>>();
-                final_row.push(row);
-            }
-            generate_combinations(&mut (final_row.join(&'?')), 0, final_valid, &mut cache)
+/// What the error says when the arrangements do not fit in `usize`.
+const OVERFLOW: &str = "expected the arrangements to fit in usize";
+
+/// The possible arrangements of the row on line `line_number`, unfolded
+/// `copies` times, `None` when they do not fit in `usize`.
+fn arrangements(line_number: usize, row: &str, valids: &[usize], copies: usize) -> Option<usize> {
+    // one cache per row, the rows rarely share an arrangement
+    let mut cache = Cache::default();
+    let row = row.chars().collect::<Vec<char>>();
+    let final_row = vec![row; copies].join(&'?');
+    let final_valid = valids.repeat(copies);
+    let arrangements = generate_combinations(&final_row, 0, final_valid, &mut cache);
+    debug!(
+        line = line_number,
+        arrangements, cache.hits, cache.misses, "row"
+    );
+    arrangements
+}
+
+/// Sum of the possible arrangements of every row.
+pub fn day12_part1(input: impl BufRead) -> Result<usize, InputError> {
+    Line::sum_each(12, input, |line| {
+        let (row, valids) = record(line)?;
+        arrangements(line.number, row, &valids, 1).ok_or_else(|| line.error(row, OVERFLOW))
+    })
+}
+
+/// Sum of the arrangements of every row unfolded five times.
+pub fn day12_part2(input: impl BufRead) -> Result<usize, InputError> {
+    Line::sum_each(12, input, |line| {
+        let (row, valids) = record(line)?;
+        arrangements(line.number, row, &valids, 5).ok_or_else(|| line.error(row, OVERFLOW))
+    })
+}
+
+/// Sum of the arrangements of the parsed records, each unfolded `copies`
+/// times.
+pub fn total_arrangements(records: &[Record], copies: usize) -> Result<usize, ParseError> {
+    (1..)
+        .zip(records)
+        .try_fold(0usize, |sum, (line, (row, valids))| {
+            arrangements(line, row, valids, copies)
+                .and_then(|count| sum.checked_add(count))
+                .ok_or_else(|| ParseError::new(12, line, 1, row, OVERFLOW))
         })
-        .sum()
+}
+
+/// [`Solution`] for day 12.
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Input = Vec<Record>;
+
+    fn parse(input: &str) -> anyhow::Result<Vec<Record>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(records: &Vec<Record>) -> anyhow::Result<Answer> {
+        Ok(total_arrangements(records, 1)?.into())
+    }
+
+    fn part2(records: &Vec<Record>) -> anyhow::Result<Answer> {
+        Ok(total_arrangements(records, 5)?.into())
+    }
+
+    fn solve_reader(reader: &mut dyn BufRead, part: u8) -> anyhow::Result<Answer> {
+        match part {
+            1 => Ok(day12_part1(reader)?.into()),
+            2 => Ok(day12_part2(reader)?.into()),
+            _ => bail!("part {part} does not exist"),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-
-    #[test]
-    fn day12_part1_small_test() {
-        let test = String::from(
-            r"???.### 1,1,3
-.??..??...?##. 1,1,3
-?#?#?#?#?#?#?#? 1,3,1,6
-????.#...#... 4,1,1
-????.######..#####. 1,6,5
-?###???????? 3,2,1",
-        );
-        assert_eq!(day12_part1(test), 21);
-    }
+    use insta::assert_debug_snapshot;
 
     #[test]
     fn day12_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day12/input.txt")?;
+        let input = test_input(12)?;
 
-        let r = day12_part1(input);
+        let r = day12_part1(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 7705);
         Ok(())
     }
-
-    #[test]
-    fn day12_part2_small_test() {
-        let test = String::from(
-            r"???.### 1,1,3
-.??..??...?##. 1,1,3
-?#?#?#?#?#?#?#? 1,3,1,6
-????.#...#... 4,1,1
-????.######..#####. 1,6,5
-?###???????? 3,2,1",
-        );
-        assert_eq!(day12_part2(test), 525152);
-    }
     #[test]
     fn day12_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day12/input.txt")?;
-        let r = day12_part2(input);
+        let input = test_input(12)?;
+        let r = day12_part2(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 50338344809230);
         Ok(())
     }
+
+    #[test]
+    fn day12_overflow() {
+        let row = format!("{} {}", "?".repeat(130), vec!["1"; 40].join(","));
+        let Err(InputError::Parse(error)) = day12_part1(row.as_bytes()) else {
+            panic!("expected the arrangements to overflow");
+        };
+        assert_eq!((error.line, error.column), (1, 1));
+        assert_eq!(error.message, OVERFLOW);
+        let records = parse(&row).unwrap();
+        assert_eq!(total_arrangements(&records, 1), Err(error));
+    }
+
+    #[test]
+    fn day12_snapshot() -> Result<()> {
+        assert_debug_snapshot!("records", parse(&fixture(12, "example")?)?);
+        Ok(())
+    }
 }
@@ -1,145 +1,307 @@
-use std::{collections::HashMap, usize};
-mod refactor;
+//! Day 2: Cube Conundrum.
 
-fn day2_part1(
-    input: String,
+pub mod refactor;
+
+use std::io::BufRead;
+
+use anyhow::bail;
+use tracing::trace;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alpha1, space1},
+    combinator::{cut, map_opt, verify},
+    multi::separated_list1,
+    sequence::{preceded, separated_pair, tuple},
+};
+
+use crate::{
+    error::{InputError, Line, ParseError},
+    parsing::{parse_line, unsigned, IResult},
+    solution::{Answer, Implementation, Solution},
+};
+
+/// What a game line looks like, for the parse errors.
+const GAME: &str = "a game like `Game 1: 3 blue, 4 red; 2 green`";
+
+/// The colours of the cubes.
+const COLOURS: [&str; 3] = ["red", "green", "blue"];
+
+/// The cubes shown in a game, as `(ammount, colour)`.
+pub type Cubes = Vec<(usize, &'static str)>;
+
+/// One of the [`COLOURS`].
+fn colour(input: &str) -> IResult<'_, &'static str> {
+    map_opt(alpha1, |colour: &str| {
+        COLOURS.into_iter().find(|known| *known == colour)
+    })(input)
+}
+
+/// Splits a game line into its cubes.
+fn cubes(line: Line<'_>) -> Result<Cubes, ParseError> {
+    let id = line.number;
+    parse_line(
+        &line,
+        GAME,
+        preceded(
+            tuple((
+                tag("Game "),
+                verify(unsigned::<usize>, |n| *n == id),
+                tag(": "),
+            )),
+            separated_list1(
+                alt((tag(", "), tag("; "))),
+                cut(separated_pair(unsigned, space1, colour)),
+            ),
+        ),
+    )
+}
+
+/// Parses the games, the game on line `n` has the id `n`.
+pub fn parse(input: &str) -> Result<Vec<Cubes>, ParseError> {
+    Line::lines(2, input).map(cubes).collect()
+}
+
+/// The id of `game` if it is possible with the given amount of cubes, 0
+/// otherwise.
+fn possible(
+    game: usize,
+    cubes: &[(usize, &str)],
     ammount_red: usize,
     ammount_blue: usize,
     ammount_green: usize,
 ) -> usize {
-    let mut game: usize = 0;
-    input
-        .lines()
-        .map(|line| {
-            game += 1;
-            let sets: Vec<&str> = line
-                .strip_prefix(format!("Game {game}: ").as_str())
-                .expect("Wrong line format")
-                .split(";")
-                .collect();
-            for set in sets {
-                let cubes: Vec<&str> = set.split(',').collect();
-                for cube in cubes {
-                    let (ammount, colour) = cube
-                        .trim()
-                        .split_once(' ')
-                        .expect("wrong number - colour format");
-                    let ammount = ammount.parse::<usize>().expect("wrong number of cubes");
-                    match colour {
-                        "red" => {
-                            if ammount > ammount_red {
-                                return 0;
-                            }
-                        }
-                        "blue" => {
-                            if ammount > ammount_blue {
-                                return 0;
-                            }
-                        }
-                        "green" => {
-                            if ammount > ammount_green {
-                                return 0;
-                            }
-                        }
-                        _ => (),
-                    }
-                }
+    for &(ammount, colour) in cubes {
+        match colour {
+            "red" if ammount > ammount_red => {
+                trace!(game, ammount, colour, "impossible game");
+                return 0;
             }
-            game
-        })
-        .sum()
+            "blue" if ammount > ammount_blue => {
+                trace!(game, ammount, colour, "impossible game");
+                return 0;
+            }
+            "green" if ammount > ammount_green => {
+                trace!(game, ammount, colour, "impossible game");
+                return 0;
+            }
+            _ => (),
+        }
+    }
+    game
 }
 
-fn day2_part2(input: String) -> usize {
-    let mut game: usize = 0;
-    input
-        .lines()
-        .map(|line| {
-            game += 1;
-            let sets: Vec<&str> = line
-                .strip_prefix(format!("Game {game}: ").as_str())
-                .expect("Wrong line format")
-                .split(";")
-                .collect();
-            let mut min_red = 0;
-            let mut min_blue = 0;
-            let mut min_green = 0;
-            for set in sets {
-                let cubes: Vec<&str> = set.split(',').collect();
-                for cube in cubes {
-                    let (ammount, colour) = cube
-                        .trim()
-                        .split_once(' ')
-                        .expect("wrong number - colour format");
-                    let ammount = ammount.parse::<usize>().expect("wrong number of cubes");
-                    match colour {
-                        "red" => {
-                            min_red = min_red.max(ammount);
-                        }
-                        "blue" => {
-                            min_blue = min_blue.max(ammount);
-                        }
-                        "green" => {
-                            min_green = min_green.max(ammount);
-                        }
-                        _ => (),
-                    }
-                }
+/// The power of the minimum set of cubes of `game`.
+fn power(game: usize, cubes: &[(usize, &str)]) -> usize {
+    let mut min_red = 0;
+    let mut min_blue = 0;
+    let mut min_green = 0;
+    for &(ammount, colour) in cubes {
+        match colour {
+            "red" => {
+                min_red = min_red.max(ammount);
             }
-            min_green * min_blue * min_red
-        })
+            "blue" => {
+                min_blue = min_blue.max(ammount);
+            }
+            "green" => {
+                min_green = min_green.max(ammount);
+            }
+            _ => (),
+        }
+    }
+    trace!(game, min_red, min_green, min_blue, "minimum set");
+    min_green * min_blue * min_red
+}
+
+/// Sum of the ids of the games possible with the given amount of cubes.
+pub fn day2_part1(
+    input: impl BufRead,
+    ammount_red: usize,
+    ammount_blue: usize,
+    ammount_green: usize,
+) -> Result<usize, InputError> {
+    Line::sum_each(2, input, |line| {
+        let cubes = cubes(line)?;
+        Ok(possible(
+            line.number,
+            &cubes,
+            ammount_red,
+            ammount_blue,
+            ammount_green,
+        ))
+    })
+}
+
+/// Sum of the power of the minimum set of cubes of every game.
+pub fn day2_part2(input: impl BufRead) -> Result<usize, InputError> {
+    Line::sum_each(2, input, |line| Ok(power(line.number, &cubes(line)?)))
+}
+
+/// [`day2_part1`] on the parsed games.
+pub fn possible_games(
+    games: &[Cubes],
+    ammount_red: usize,
+    ammount_blue: usize,
+    ammount_green: usize,
+) -> usize {
+    (1..)
+        .zip(games)
+        .map(|(id, cubes)| possible(id, cubes, ammount_red, ammount_blue, ammount_green))
         .sum()
 }
 
+/// [`day2_part2`] on the parsed games.
+pub fn powers(games: &[Cubes]) -> usize {
+    (1..).zip(games).map(|(id, cubes)| power(id, cubes)).sum()
+}
+
+/// [`Solution`] for day 2.
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    type Input = Vec<Cubes>;
+    const IMPLEMENTATIONS: &'static [Implementation] = &[
+        Implementation {
+            name: "refactor",
+            part: 1,
+            solve: |input| Ok(refactor::day2_part1(input.as_bytes(), 12, 14, 13)?.into()),
+        },
+        Implementation {
+            name: "refactor",
+            part: 2,
+            solve: |input| Ok(refactor::day2_part2(input.as_bytes())?.into()),
+        },
+    ];
+
+    fn parse(input: &str) -> anyhow::Result<Vec<Cubes>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(games: &Vec<Cubes>) -> anyhow::Result<Answer> {
+        Ok(possible_games(games, 12, 14, 13).into())
+    }
+
+    fn part2(games: &Vec<Cubes>) -> anyhow::Result<Answer> {
+        Ok(powers(games).into())
+    }
+
+    fn solve_reader(reader: &mut dyn BufRead, part: u8) -> anyhow::Result<Answer> {
+        match part {
+            1 => Ok(day2_part1(reader, 12, 14, 13)?.into()),
+            2 => Ok(day2_part2(reader)?.into()),
+            _ => bail!("part {part} does not exist"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
+    use insta::assert_debug_snapshot;
+    use proptest::prelude::*;
     #[test]
-    fn day2_part1_small_test() {
-        let test = String::from(
-            r"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
-        );
-        assert_eq!(day2_part1(test.clone(), 12, 14, 13), 8);
-        assert_eq!(refactor::day2_part1(test, 12, 14, 13), 8);
+    fn day2_refactor_small_test() -> Result<()> {
+        let test = fixture(2, "example")?;
+        assert_eq!(refactor::day2_part1(test.as_bytes(), 12, 14, 13)?, 8);
+        assert_eq!(refactor::day2_part2(test.as_bytes())?, 2286);
+        Ok(())
     }
 
     #[test]
     fn day2_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day2/input.txt")?;
+        let input = test_input(2)?;
 
-        let r = refactor::day2_part1(input.clone(), 12, 14, 13);
+        let r = day2_part1(input.as_bytes(), 12, 14, 13)?;
         println!("{}", r);
         assert_eq!(r, 2551);
-        assert_eq!(refactor::day2_part1(input, 12, 14, 13), 2551);
+        assert_eq!(refactor::day2_part1(input.as_bytes(), 12, 14, 13)?, 2551);
+        Ok(())
+    }
+
+    #[test]
+    fn day2_part2_test() -> Result<()> {
+        let input = test_input(2)?;
+        let r = day2_part2(input.as_bytes())?;
+        println!("{}", r);
+        assert_eq!(r, 62811);
+        assert_eq!(refactor::day2_part2(input.as_bytes())?, 62811);
         Ok(())
     }
 
+    #[test]
+    fn day2_parse_error() {
+        let test = "Game 1: 3 blue\nGame 2: 3 blue, x red".as_bytes();
+        let Err(InputError::Parse(error)) = day2_part2(test) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 17, "x")
+        );
+        assert!(matches!(refactor::day2_part2(test), Err(InputError::Parse(e)) if e == error));
+    }
+
+    #[test]
+    fn day2_unknown_colour() {
+        let test = "Game 1: 3 blue; 2 purple, 1 red".as_bytes();
+        let Err(InputError::Parse(error)) = day2_part1(test, 12, 14, 13) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((error.column, error.text.as_str()), (19, "purple"));
+        assert!(
+            matches!(refactor::day2_part1(test, 12, 14, 13), Err(InputError::Parse(e)) if e == error)
+        );
+        assert!(day2_part2(test).is_err());
+        assert!(refactor::day2_part2(test).is_err());
+    }
+
+    /// Game lines numbered from 1, some of the games miss a colour.
+    fn games() -> impl Strategy<Value = String> {
+        let cube = (1..=20usize, prop::sample::select(COLOURS.to_vec()));
+        let set = prop::collection::vec(cube, 1..4).prop_map(|cubes| {
+            cubes
+                .iter()
+                .map(|(ammount, colour)| format!("{ammount} {colour}"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        });
+        let game = prop::collection::vec(set, 1..5).prop_map(|sets| sets.join("; "));
+        prop::collection::vec(game, 1..10).prop_map(|games| {
+            games
+                .iter()
+                .enumerate()
+                .map(|(i, game)| format!("Game {}: {game}", i + 1))
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+    }
+
+    proptest! {
         #[test]
-        fn day2_part2_small_test() {
-            let test = String::from(
-                r"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        fn day2_refactor_agrees(input in games(), limits in (0..20usize, 0..20usize, 0..20usize)) {
+            let (red, blue, green) = limits;
+            let input = input.as_bytes();
+            prop_assert_eq!(
+                day2_part1(input, red, blue, green).unwrap(),
+                refactor::day2_part1(input, red, blue, green).unwrap()
             );
-            assert_eq!(refactor::day2_part2(test.clone()),2286);
-            assert_eq!(day2_part2(test),2286);
+            prop_assert_eq!(day2_part2(input).unwrap(), refactor::day2_part2(input).unwrap());
+            let games = parse(std::str::from_utf8(input).unwrap()).unwrap();
+            prop_assert_eq!(possible_games(&games, red, blue, green), day2_part1(input, red, blue, green).unwrap());
+            prop_assert_eq!(powers(&games), day2_part2(input).unwrap());
         }
+    }
 
-        #[test]
-        fn day2_part2_test() -> Result<()> {
-            let input = fs::read_to_string("./src/day2/input.txt")?;
-            let r = refactor::day2_part2(input.clone());
-            println!("{}", r);
-            assert_eq!(r, 62811);
-            assert_eq!(refactor::day2_part2(input), 62811);
-            Ok(())
-        }
+    #[test]
+    fn day2_snapshot() -> Result<()> {
+        let input = fixture(2, "example")?;
+        assert_debug_snapshot!("cubes", parse(&input)?);
+        assert_debug_snapshot!("games", refactor::parse(&input)?);
+        Ok(())
+    }
 }
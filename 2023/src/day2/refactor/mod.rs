@@ -1,56 +1,86 @@
-use std::collections::HashMap;
+//! Day 2 parsed into games, sets and cubes.
 
-#[derive(Debug)]
-struct Cube<'a> {
-    colour: &'a str,
-    ammount: usize,
+use std::{collections::HashMap, io::BufRead};
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::space1,
+    combinator::{cut, map, verify},
+    multi::separated_list1,
+    sequence::{preceded, separated_pair, tuple},
+};
+
+use super::{colour, COLOURS, GAME};
+use crate::{
+    error::{InputError, Line, ParseError},
+    parsing::{parse_line, unsigned, IResult},
+};
+
+/// A number of cubes of one colour: `3 blue`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cube<'a> {
+    /// Colour name, `red`, `green` or `blue`.
+    pub colour: &'a str,
+    /// How many cubes of the colour.
+    pub ammount: usize,
 }
 
-impl<'a> Cube<'a> {
-    fn parse(input: &'a str) -> Cube {
-        let (ammount, colour) = input
-            .trim()
-            .split_once(' ')
-            .expect("wrong number - colour format");
-        Cube {
-            colour,
-            ammount: ammount.parse().expect("wrong ammount number"),
-        }
-    }
+/// Parses `3 blue`.
+pub fn cube(input: &str) -> IResult<'_, Cube<'_>> {
+    map(
+        separated_pair(unsigned, space1, colour),
+        |(ammount, colour)| Cube { colour, ammount },
+    )(input)
 }
 
-#[derive(Debug)]
-struct Sets<'a> {
-    cubes: Vec<Cube<'a>>,
+/// The cubes shown at once: `3 blue, 4 red`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sets<'a> {
+    /// The cubes of each colour.
+    pub cubes: Vec<Cube<'a>>,
 }
-impl<'a> Sets<'a> {
-    fn parse(input: &'a str) -> Sets {
-        Sets {
-            cubes: input.split(',').map(Cube::parse).collect(),
-        }
-    }
+
+/// Parses `3 blue, 4 red`.
+pub fn sets(input: &str) -> IResult<'_, Sets<'_>> {
+    map(separated_list1(tag(", "), cut(cube)), |cubes| Sets {
+        cubes,
+    })(input)
+}
+
+/// Parses the game line numbered `id`.
+pub fn game<'a>(id: usize) -> impl FnMut(&'a str) -> IResult<'a, Game<'a>> {
+    map(
+        preceded(
+            tuple((
+                tag("Game "),
+                verify(unsigned::<usize>, move |n| *n == id),
+                tag(": "),
+            )),
+            separated_list1(tag("; "), cut(sets)),
+        ),
+        move |sets| Game { id, sets },
+    )
 }
 
-#[derive(Debug)]
-struct Game<'a> {
-    id: usize,
-    sets: Vec<Sets<'a>>,
+/// A game line: `Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game<'a> {
+    /// The game number.
+    pub id: usize,
+    /// The sets revealed, separated by `;`.
+    pub sets: Vec<Sets<'a>>,
 }
 
 impl<'a> Game<'a> {
-    fn parse(input: &'a str, id: usize) -> Game {
-        Game {
-            id,
-            sets: input
-                .strip_prefix(format!("Game {id}: ").as_str())
-                .expect("Wrong line format")
-                .split(';')
-                .map(Sets::parse)
-                .collect(),
-        }
+    /// Parses a game line, the game id is the line number.
+    pub fn parse(line: Line<'a>) -> Result<Game<'a>, ParseError> {
+        parse_line(&line, GAME, game(line.number))
     }
 
-    fn is_valid(&self, valid_values: &HashMap<&str, usize>) -> Option<usize> {
+    /// The game id if no set shows more cubes than `valid_values` allows.
+    ///
+    /// Panics on a colour missing from `valid_values`.
+    pub fn is_valid(&self, valid_values: &HashMap<&str, usize>) -> Option<usize> {
         self.sets
             .iter()
             .all(|set| {
@@ -60,7 +90,10 @@ impl<'a> Game<'a> {
             })
             .then_some(self.id)
     }
-    fn get_power_set(&self) -> usize {
+
+    /// Product of the minimum number of cubes of each colour, a colour never
+    /// shown needs none.
+    pub fn get_power_set(&self) -> usize {
         let mut min_values: HashMap<&str, usize> = HashMap::new();
         self.sets.iter().for_each(|set| {
             set.cubes.iter().for_each(|cube| {
@@ -70,35 +103,33 @@ impl<'a> Game<'a> {
                     .or_insert(cube.ammount);
             });
         });
-        min_values.values().product()
+        COLOURS
+            .iter()
+            .map(|colour| min_values.get(colour).copied().unwrap_or(0))
+            .product()
     }
 }
 
-fn parse<'a>(input: &'a String) -> Vec<Game<'a>> {
-    let mut game: usize = 0;
-    input
-        .lines()
-        .map(|line| {
-            game += 1;
-            Game::parse(line, game)
-        })
-        .collect()
+/// Parses every game, numbered from 1.
+pub fn parse(input: &str) -> Result<Vec<Game<'_>>, ParseError> {
+    Line::lines(2, input).map(Game::parse).collect()
 }
 
-pub fn day2_part1(input: String, reds: usize, blues: usize, greens: usize) -> usize {
+/// Same as [`super::day2_part1`].
+pub fn day2_part1(
+    input: impl BufRead,
+    reds: usize,
+    blues: usize,
+    greens: usize,
+) -> Result<usize, InputError> {
     let valid_values = HashMap::from([("red", reds), ("green", greens), ("blue", blues)]);
 
-    let games = parse(&input);
-    games
-        .iter()
-        .filter_map(|game| game.is_valid(&valid_values))
-        .sum()
+    Line::sum_each(2, input, |line| {
+        Ok(Game::parse(line)?.is_valid(&valid_values).unwrap_or(0))
+    })
 }
 
-pub fn day2_part2(input: String) -> usize {
-    let games = parse(&input);
-    games
-        .iter()
-        .map(|game| game.get_power_set())
-        .sum()
+/// Same as [`super::day2_part2`].
+pub fn day2_part2(input: impl BufRead) -> Result<usize, InputError> {
+    Line::sum_each(2, input, |line| Ok(Game::parse(line)?.get_power_set()))
 }
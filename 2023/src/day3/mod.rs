@@ -1,177 +1,144 @@
-use std::{collections::HashMap, usize};
+//! Day 3: Gear Ratios.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use tracing::{debug, trace};
+
+use crate::{
+    error::ParseError,
+    grid::{Grid, Pos},
+    solution::{Answer, Solution},
+};
 
 const POINT: char = '.';
 const STAR: char = '*';
 
-fn get_map_numbers(input: String) -> (Vec<Vec<char>>, HashMap<(usize, usize), String>) {
-    let lines: Vec<&str> = input.lines().collect();
-    let mut numbers: HashMap<(usize, usize), String> = HashMap::new();
-    let mut map: Vec<Vec<char>> = Vec::with_capacity(lines.len());
-    for (i, line) in lines.iter().enumerate() {
+/// The numbers of the schematic by the position of their first digit.
+type Numbers = BTreeMap<Pos, String>;
+
+/// The part numbers next to each `*`, by its position.
+type Gears = BTreeMap<Pos, Vec<usize>>;
+
+fn get_map_numbers(map: &Grid<char>) -> Numbers {
+    let mut numbers = Numbers::new();
+    for (i, row) in map.rows().enumerate() {
         let mut number = String::new();
-        map.push(line.chars().collect());
-        for (j, c) in line.chars().enumerate() {
-            if c.is_digit(10) {
-                number.push(c);
-            } else if number.len() > 0 {
+        for (j, c) in row.iter().enumerate() {
+            if c.is_ascii_digit() {
+                number.push(*c);
+            } else if !number.is_empty() {
                 numbers.insert((i, j - number.len()), number.clone());
                 number = String::new();
             }
         }
-        if number.len() > 0 {
-            numbers.insert((i, line.len() - number.len()), number.clone());
+        if !number.is_empty() {
+            numbers.insert((i, row.len() - number.len()), number.clone());
         }
     }
 
-    (map, numbers)
+    numbers
 }
 
-fn check_range(
-    map: &Vec<Vec<char>>,
-    value: usize,
-    x: usize,
-    start: usize,
-    end: usize,
-    gears: &mut HashMap<(usize, usize), Vec<usize>>,
-) -> bool {
-    let row = map.get(x).expect("wrong row");
-    for (gy, c) in row
-        .get(start..end)
-        .expect("wrong columns")
-        .iter()
-        .enumerate()
-    {
-        if check_value(c, x, gy + start, value, gears) {
-            return true;
-        }
-    }
-    return false;
+fn is_symbol(value: char) -> bool {
+    !value.is_ascii_digit() && value != POINT
 }
 
-fn check_value(
-    value: &char,
-    x: usize,
-    y: usize,
-    num: usize,
-    gears: &mut HashMap<(usize, usize), Vec<usize>>,
-) -> bool {
-    if !value.is_digit(10) && *value != POINT {
-        if *value == STAR {
-            gears
-                .entry((x, y))
-                .and_modify(|e| e.push(num))
-                .or_insert(vec![num]);
+/// The part numbers, the numbers next to a symbol, in reading order, and the
+/// part numbers next to each `*`.
+fn part_numbers(map: &Grid<char>) -> Result<(Vec<usize>, Gears), ParseError> {
+    let mut gears = Gears::new();
+    let numbers = get_map_numbers(map);
+    debug!(numbers = numbers.len(), "numbers found");
+    let mut parts = vec![];
+    for (key, value) in numbers.iter() {
+        let num = value
+            .parse::<usize>()
+            .map_err(|_| ParseError::new(3, key.0 + 1, key.1 + 1, value, "expected a number"))?;
+        let symbols = (key.1..key.1 + value.len())
+            .flat_map(|column| map.neighbours8((key.0, column)))
+            .filter(|pos| is_symbol(map[*pos]))
+            .collect::<BTreeSet<Pos>>();
+        if symbols.is_empty() {
+            continue;
+        }
+        trace!(row = key.0, column = key.1, num, "part number");
+        parts.push(num);
+        for star in symbols.into_iter().filter(|pos| map[*pos] == STAR) {
+            gears.entry(star).or_default().push(num);
         }
-        return true;
     }
-    false
+    Ok((parts, gears))
 }
 
-fn day3(input: String) -> (usize, usize) {
-    let mut gears: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
-    let (map, numbers) = get_map_numbers(input);
-    let mut sum = 0;
-    'main: for (key, value) in numbers.iter() {
-        let num = value.parse::<usize>().expect("wrong number format");
-        let row = map.get(key.0).expect("wrong row");
-
-        // left same row
-        if key.1 > 0 {
-            let l_v = row.get(key.1 - 1).unwrap_or(&POINT);
-            if check_value(l_v, key.0, key.1 - 1, num, &mut gears) {
-                sum += num;
-                continue;
-            }
-        }
-
-        // right same row
-        let r_v = row.get(key.1 + value.len()).unwrap_or(&POINT);
-        if check_value(r_v, key.0, key.1 + value.len(), num, &mut gears) {
-            sum += num;
-            continue;
-        }
+/// Sum of the part numbers and sum of the gear ratios of the schematic.
+pub fn day3(input: &str) -> Result<(usize, usize), ParseError> {
+    sums(&Grid::parse(3, input)?)
+}
 
-        let start = key.1.saturating_sub(1);
-        let mut end = start + value.len() + 2;
-        end = if end < row.len() { end } else { row.len() };
-        
-        // up
-        if key.0 > 0 {
-            if check_range(&map, num, key.0 - 1, start, end, &mut gears) {
-                sum += num;
-                continue 'main;
-            }
-        }
-        //down
-        if key.0 < map.len() - 1 {
-            if check_range(&map, num, key.0 + 1, start, end, &mut gears) {
-                sum += num;
-                continue 'main;
-            }
-        }
-    }
+/// [`day3`] on the parsed schematic.
+pub fn sums(map: &Grid<char>) -> Result<(usize, usize), ParseError> {
+    let (parts, gears) = part_numbers(map)?;
     let gears_sum = gears
         .iter()
         .filter(|(_, v)| v.len() > 1)
-        .map(|(_, v)| v.iter().product::<usize>())
+        .map(|(star, v)| {
+            trace!(row = star.0, column = star.1, numbers = ?v, "gear");
+            v.iter().product::<usize>()
+        })
         .sum();
-    (sum, gears_sum)
+    Ok((parts.iter().sum(), gears_sum))
+}
+
+/// [`Solution`] for day 3.
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    type Input = Grid<char>;
+
+    fn parse(input: &str) -> anyhow::Result<Grid<char>> {
+        Ok(Grid::parse(3, input)?)
+    }
+
+    fn part1(map: &Grid<char>) -> anyhow::Result<Answer> {
+        Ok(sums(map)?.0.into())
+    }
+
+    fn part2(map: &Grid<char>) -> anyhow::Result<Answer> {
+        Ok(sums(map)?.1.into())
+    }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-    #[test]
-    fn day3_part1_small_test() {
-        let test = String::from(
-            r"467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598..",
-        );
-        assert_eq!(day3(test).0, 4361);
-    }
-
+    use insta::assert_debug_snapshot;
     #[test]
     fn day3_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day3/input.txt")?;
+        let input = test_input(3)?;
 
-        let r = day3(input).0;
+        let r = day3(&input)?.0;
         println!("{}", r);
         assert_eq!(r, 529618);
         Ok(())
     }
-
-    #[test]
-    fn day3_part2_small_test() {
-        let test = String::from(
-            r"467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598..",
-        );
-        assert_eq!(day3(test).1, 467835);
-    }
     //
     #[test]
     fn day3_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day3/input.txt")?;
-        let r = day3(input).1;
+        let input = test_input(3)?;
+        let r = day3(&input)?.1;
         println!("{}", r);
         assert_eq!(r, 77509019);
         Ok(())
     }
+
+    #[test]
+    fn day3_snapshot() -> Result<()> {
+        let map = Grid::parse(3, &fixture(3, "example")?)?;
+        assert_debug_snapshot!("numbers", get_map_numbers(&map));
+        assert_debug_snapshot!("part_numbers", part_numbers(&map)?);
+        Ok(())
+    }
 }
@@ -1,47 +1,57 @@
-use std::{collections::HashMap, str};
+//! Day 4: Scratchcards.
+
+use std::{collections::VecDeque, io::BufRead};
+
+use anyhow::bail;
+use tracing::trace;
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{self, digit1, line_ending, space0, space1},
-    multi::{fold_many1, separated_list1},
-    sequence::{delimited, separated_pair, terminated, tuple},
-    IResult, Parser,
+    character::complete::{char, space0, space1},
+    sequence::{delimited, separated_pair, tuple},
+    Parser,
+};
+
+use crate::{
+    error::{InputError, Line, ParseError},
+    parsing::{parse_line, parse_lines, unsigned, unsigned_list, IResult},
+    solution::{Answer, Solution},
 };
 
-#[derive(Debug)]
-struct Card<'a> {
-    id: &'a str,
-    elf_numbers: Vec<u32>,
-    winning_numbers: Vec<u32>,
+/// A scratchcard: `Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    /// The card number.
+    pub id: usize,
+    /// The numbers after the `|`.
+    pub elf_numbers: Vec<u32>,
+    /// The numbers before the `|`.
+    pub winning_numbers: Vec<u32>,
 }
 
-impl<'a> Card<'a> {
-    fn get_win_count(&self) -> usize {
+impl Card {
+    /// How many of the elf numbers are winning numbers.
+    pub fn get_win_count(&self) -> usize {
         self.elf_numbers
             .iter()
-            .filter(|number| self.winning_numbers.contains(&number))
+            .filter(|number| self.winning_numbers.contains(number))
             .count()
     }
 }
 
-fn nums(input: &str) -> IResult<&str, Vec<u32>> {
-    fold_many1(
-        terminated(complete::u32, space0),
-        Vec::new,
-        |mut acc: Vec<_>, item| {
-            acc.push(item);
-            acc
-        },
-    )(input)
+/// Parses space separated numbers, allowing padding between them.
+pub fn nums(input: &str) -> IResult<'_, Vec<u32>> {
+    unsigned_list(input)
 }
 
-fn card(input: &str) -> IResult<&str, Card> {
+/// Parses a single card line.
+pub fn card(input: &str) -> IResult<'_, Card> {
     let (input, id) = delimited(
         tuple((tag("Card"), space1)),
-        digit1,
-        tuple((tag(":"), space1)),
+        unsigned,
+        tuple((char(':'), space0)),
     )(input)?;
-    separated_pair(nums, tuple((tag("|"), space1)), nums)
+    separated_pair(nums, char('|'), nums)
         .map(|(winning_numbers, elf_numbers)| Card {
             id,
             elf_numbers,
@@ -50,90 +60,165 @@ fn card(input: &str) -> IResult<&str, Card> {
         .parse(input)
 }
 
-fn parse(input: &str) -> Vec<Card> {
-    let (_, games) = separated_list1(line_ending, card)(input).expect("should parse");
-    games
+/// What a card line looks like, for the parse errors.
+const CARD: &str = "`Card <id>: <numbers> | <numbers>`";
+
+/// Parses every card of the input.
+pub fn parse(input: &str) -> Result<Vec<Card>, ParseError> {
+    parse_lines(4, input, CARD, card)
 }
 
-fn day4_part1(input: String) -> usize {
-    let cards = parse(input.as_str());
-    cards
-        .iter()
-        .map(|card| {
-            let count = card.get_win_count() as u32;
-            if count > 0 {
-                2usize.pow(count - 1)
-            } else {
-                0
-            }
+/// Checks that the cards are numbered from 1 in order, part 2 wins copies of
+/// the cards that follow a card in the input.
+pub fn validate(input: &str) -> Result<Vec<ParseError>, ParseError> {
+    let cards = parse(input)?;
+    Ok(Line::lines(4, input)
+        .zip(cards)
+        .zip(1..)
+        .filter(|((_, card), expected)| card.id != *expected)
+        .map(|((line, _), expected)| {
+            let id = line.text["Card".len()..].trim_start();
+            let id = &id[..id.find(':').unwrap_or(id.len())];
+            line.error(id, format!("expected card {expected}"))
         })
-        .sum()
+        .collect())
 }
 
-fn day4_part2(input: String) -> usize {
-    let cards = parse(input.as_str());
-    let mut wining_copies: HashMap<usize, usize> = (0..cards.len()).map(|i| (i, 1)).collect();
-    cards.iter().for_each(|card| {
-        let id = card.id.parse::<usize>().expect("wrong id");
+/// Points of a card, its first match is worth 1 and every other one doubles
+/// them.
+fn card_points(card: &Card) -> usize {
+    let count = card.get_win_count() as u32;
+    trace!(card = card.id, wins = count, "card");
+    if count > 0 {
+        2usize.pow(count - 1)
+    } else {
+        0
+    }
+}
+
+/// Sum of the points of every card.
+pub fn points(cards: &[Card]) -> usize {
+    cards.iter().map(card_points).sum()
+}
+
+/// Counts the cards in order, only remembering the copies won of the next
+/// ones.
+#[derive(Debug, Default)]
+struct Copies {
+    won: VecDeque<usize>,
+    total: usize,
+}
+
+impl Copies {
+    fn add(&mut self, card: &Card) {
+        let instances = 1 + self.won.pop_front().unwrap_or(0);
+        self.total += instances;
         let count = card.get_win_count();
-        let current_instances = wining_copies.get(&id).unwrap_or(&1).clone();
-        for n in 1..(count + 1) {
-            wining_copies
-                .entry(n + id)
-                .and_modify(|e| *e += current_instances)
-                .or_insert(current_instances);
+        trace!(card = card.id, wins = count, instances, "card");
+        if self.won.len() < count {
+            self.won.resize(count, 0);
+        }
+        for won in self.won.iter_mut().take(count) {
+            *won += instances;
+        }
+    }
+}
+
+/// Total number of cards after winning copies of the following ones.
+pub fn copies(cards: &[Card]) -> usize {
+    let mut copies = Copies::default();
+    cards.iter().for_each(|card| copies.add(card));
+    copies.total
+}
+
+/// Total points of the scratchcards.
+pub fn day4_part1(input: impl BufRead) -> Result<usize, InputError> {
+    Line::sum_each(4, input, |line| {
+        Ok(card_points(&parse_line(&line, CARD, card)?))
+    })
+}
+
+/// Total scratchcards after winning copies.
+pub fn day4_part2(input: impl BufRead) -> Result<usize, InputError> {
+    let mut copies = Copies::default();
+    Line::read_each(4, input, |line| {
+        copies.add(&parse_line(&line, CARD, card)?);
+        Ok(())
+    })?;
+    Ok(copies.total)
+}
+
+/// [`Solution`] for day 4.
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    type Input = Vec<Card>;
+
+    fn parse(input: &str) -> anyhow::Result<Vec<Card>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(cards: &Vec<Card>) -> anyhow::Result<Answer> {
+        Ok(points(cards).into())
+    }
+
+    fn part2(cards: &Vec<Card>) -> anyhow::Result<Answer> {
+        Ok(copies(cards).into())
+    }
+
+    fn solve_reader(reader: &mut dyn BufRead, part: u8) -> anyhow::Result<Answer> {
+        match part {
+            1 => Ok(day4_part1(reader)?.into()),
+            2 => Ok(day4_part2(reader)?.into()),
+            _ => bail!("part {part} does not exist"),
         }
-    });
-    wining_copies.values().sum()
+    }
+
+    fn validate(input: &str) -> anyhow::Result<Vec<ParseError>> {
+        Ok(validate(input)?)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-    #[test]
-    fn day4_part1_small_test() {
-        let test = String::from(
-            r"Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
-        );
-        assert_eq!(day4_part1(test), 13);
-    }
-
+    use insta::assert_debug_snapshot;
     #[test]
     fn day4_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day4/input.txt")?;
+        let input = test_input(4)?;
 
-        let r = day4_part1(input);
+        let r = day4_part1(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 21088);
         Ok(())
     }
 
     #[test]
-    fn day4_part2_small_test() {
-        let test = String::from(
-            r"Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    fn day4_part2_test() -> Result<()> {
+        let input = test_input(4)?;
+        let r = day4_part2(input.as_bytes())?;
+        println!("{}", r);
+        assert_eq!(r, 6874754);
+        Ok(())
+    }
+
+    #[test]
+    fn day4_validate_test() -> Result<()> {
+        assert_eq!(validate(&test_input(4)?)?, vec![]);
+        let violations = validate("Card 1: 1 | 1\nCard   3: 2 | 2\nCard 3: 3 | 3")?;
+        assert_eq!(
+            violations,
+            vec![ParseError::new(4, 2, 8, "3", "expected card 2")]
         );
-        assert_eq!(day4_part2(test), 30);
+        Ok(())
     }
 
     #[test]
-    fn day4_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day4/input.txt")?;
-        let r = day4_part2(input);
-        println!("{}", r);
-        assert_eq!(r, 6874754);
+    fn day4_snapshot() -> Result<()> {
+        assert_debug_snapshot!("cards", parse(&fixture(4, "example")?)?);
         Ok(())
     }
 }
@@ -1,91 +1,142 @@
+//! Day 5: If You Give A Seed A Fertilizer.
+
 use std::{collections::BTreeMap, ops::Range};
 
-fn day5_part1(input: String) -> u64 {
-    let mut lines = input.lines();
-    let mut maps: Vec<BTreeMap<u64, Range<u64>>> = vec![];
-    let mut locations = lines
-        .next()
-        .expect("Wrong input")
-        .split(" ")
-        .filter_map(|n| n.parse::<u64>().ok())
-        .collect::<Vec<u64>>();
-    let mut current_map = BTreeMap::new();
-    lines.for_each(|line| {
-        if line.contains("map:") {
-            maps.push(current_map.clone());
-            current_map = BTreeMap::new();
-        } else if !line.is_empty() {
-            let line_nums = line
-                .split(" ")
-                .filter_map(|n| n.parse::<u64>().ok())
-                .collect::<Vec<u64>>();
-            let destination = line_nums.get(0).expect("Wrong line format");
-            let start_range = line_nums.get(1).expect("Wrong line format");
-            let end_range = start_range + line_nums.get(2).expect("Wrong line format");
-            current_map.insert(
-                *destination,
-                Range {
-                    start: *start_range,
-                    end: end_range,
-                },
-            );
+use anyhow::{bail, Context, Result};
+use tracing::{debug, debug_span, trace};
+
+use nom::{
+    character::complete::{space0, space1},
+    sequence::{delimited, preceded, tuple},
+};
+
+use crate::{
+    error::ParseError,
+    parsing::{header, key_values, parse_line, sections, unsigned, unsigned_list},
+    solution::{Answer, Solution},
+};
+
+/// Each map goes from the destination start to the source range.
+pub type Map = BTreeMap<u64, Range<u64>>;
+
+/// The seeds line and the maps that follow it, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Almanac {
+    /// The numbers of the `seeds:` line.
+    pub seeds: Vec<u64>,
+    /// The `x-to-y map:` blocks.
+    pub maps: Vec<Map>,
+}
+
+/// Parses the seeds and every map block.
+pub fn parse(input: &str) -> Result<Almanac, ParseError> {
+    let sections = sections(5, input);
+    let Some((seeds, blocks)) = sections.split_first() else {
+        return Err(ParseError::truncated(5, 1, "expected `seeds:`"));
+    };
+    let mut seeds_lines = seeds.iter();
+    let seeds = parse_line(
+        seeds_lines.next().expect("sections are not empty"),
+        "`seeds:` and the seed numbers",
+        key_values("seeds", unsigned_list),
+    )?;
+    let mut maps: Vec<Map> = vec![];
+    // maps usually follow a blank line, but a header can also end the seeds
+    for line in seeds_lines.chain(blocks.iter().flatten()) {
+        if parse_line(line, "a map header", header(" map:")).is_ok() {
+            maps.push(BTreeMap::new());
+            continue;
+        }
+        let (destination, start_range, length) = parse_line(
+            line,
+            "`<destination> <source> <length>`",
+            delimited(
+                space0,
+                tuple((
+                    unsigned::<u64>,
+                    preceded(space1, unsigned::<u64>),
+                    preceded(space1, unsigned::<u64>),
+                )),
+                space0,
+            ),
+        )?;
+        let end_range = start_range
+            .checked_add(length)
+            .ok_or_else(|| line.error(line.text, "range is too large"))?;
+        if destination.checked_add(length).is_none() {
+            return Err(line.error(line.text, "range is too large"));
         }
-    });
-    maps.push(current_map);
-    for map in maps {
-        locations = move_seeds(&locations, &map);
+        let Some(current_map) = maps.last_mut() else {
+            return Err(line.error(line.text, "expected a map header"));
+        };
+        current_map.insert(
+            destination,
+            Range {
+                start: start_range,
+                end: end_range,
+            },
+        );
+    }
+    Ok(Almanac { seeds, maps })
+}
+
+/// Lowest location of the seeds.
+pub fn day5_part1(input: String) -> Result<u64> {
+    lowest_location(&parse(&input)?)
+}
+
+/// Lowest location when the seeds line is a list of ranges.
+pub fn day5_part2(input: String) -> Result<u64> {
+    lowest_range_location(&parse(&input)?)
+}
+
+/// [`day5_part1`] on the parsed almanac.
+pub fn lowest_location(almanac: &Almanac) -> Result<u64> {
+    let mut locations = almanac.seeds.clone();
+    for (stage, map) in almanac.maps.iter().enumerate() {
+        locations = move_seeds(&locations, map)?;
+        debug!(stage, locations = ?locations, "map stage");
     }
-    *locations.iter().min().expect("Wrong mapping")
+    locations
+        .into_iter()
+        .min()
+        .context("the almanac has no seed")
 }
 
-fn day5_part2(input: String) -> u64 {
-    let mut lines = input.lines();
-    let mut maps: Vec<BTreeMap<u64, Range<u64>>> = vec![];
-    let mut locations = lines
-        .next()
-        .expect("Wrong input")
-        .split(" ")
-        .filter_map(|n| n.parse::<u64>().ok())
-        .collect::<Vec<u64>>()
+/// [`day5_part2`] on the parsed almanac.
+pub fn lowest_range_location(almanac: &Almanac) -> Result<u64> {
+    let Almanac { seeds, maps } = almanac;
+    if seeds.len() % 2 != 0 {
+        return Err(ParseError::new(
+            5,
+            1,
+            1,
+            format!("{} seeds", seeds.len()),
+            "expected pairs of seed start and length",
+        )
+        .into());
+    }
+    let mut locations = seeds
         .chunks(2)
-        .map(|chunk| chunk[0]..(chunk[0] + chunk[1]))
-        .collect::<Vec<Range<u64>>>();
-    let mut current_map = BTreeMap::new();
-    lines.for_each(|line| {
-        if line.contains("map:") {
-            maps.push(current_map.clone());
-            current_map = BTreeMap::new();
-        } else if !line.is_empty() {
-            let line_nums = line
-                .split(" ")
-                .filter_map(|n| n.parse::<u64>().ok())
-                .collect::<Vec<u64>>();
-            let destination = line_nums.get(0).expect("Wrong line format");
-            let start_range = line_nums.get(1).expect("Wrong line format");
-            let end_range = start_range + line_nums.get(2).expect("Wrong line format");
-            current_map.insert(
-                *destination,
-                Range {
-                    start: *start_range,
-                    end: end_range,
-                },
-            );
-        }
-    });
-    maps.push(current_map);
+        .map(|chunk| match chunk[0].checked_add(chunk[1]) {
+            Some(end) => Ok(chunk[0]..end),
+            None => bail!("the seed range {} {} overflows u64", chunk[0], chunk[1]),
+        })
+        .collect::<Result<Vec<Range<u64>>>>()?;
 
-    for map in maps {
+    for (stage, map) in maps.iter().enumerate() {
+        let _span = debug_span!("map stage", stage).entered();
         let mut new_ranges = vec![];
-        while locations.len() > 0 {
-            let range = locations.pop().expect("Wrong input");
+        while let Some(range) = locations.pop() {
             let mut matched = false;
-            for (dest, map_range) in &map {
+            for (dest, map_range) in map {
                 let overlap_start = range.start.max(map_range.start);
                 let overlap_end = range.end.min(map_range.end);
                 if overlap_start < overlap_end {
+                    trace!(?range, ?map_range, dest, "overlap");
                     new_ranges.push(Range {
-                        start: (overlap_start - map_range.start + dest),
-                        end: overlap_end - map_range.start + dest,
+                        start: map_to(*dest, map_range, overlap_start)?,
+                        end: map_to(*dest, map_range, overlap_end)?,
                     });
                     if overlap_start > range.start {
                         locations.push(Range {
@@ -107,22 +158,31 @@ fn day5_part2(input: String) -> u64 {
                 new_ranges.push(range);
             }
         }
+        debug!(ranges = new_ranges.len(), "stage done");
         locations = new_ranges;
     }
     locations
         .iter()
         .map(|loc| loc.start)
         .min()
-        .expect("Should have a min location")
+        .context("the almanac has no seed")
+}
+
+/// Where `value`, at most the end of the `source` range, goes when the
+/// range starts at `destination`.
+fn map_to(destination: u64, source: &Range<u64>, value: u64) -> Result<u64> {
+    destination
+        .checked_add(value - source.start)
+        .with_context(|| format!("mapping {value} to {destination} overflows u64"))
 }
 
-fn move_seeds(mut seeds: &Vec<u64>, map: &BTreeMap<u64, Range<u64>>) -> Vec<u64> {
+fn move_seeds(seeds: &[u64], map: &BTreeMap<u64, Range<u64>>) -> Result<Vec<u64>> {
     let mut moved = vec![];
     for seed in seeds.iter() {
         let mut included = false;
         for (dest, range) in map {
             if range.contains(seed) {
-                moved.push(dest + (seed - range.start));
+                moved.push(map_to(*dest, range, *seed)?);
                 included = true;
                 break;
             }
@@ -131,110 +191,97 @@ fn move_seeds(mut seeds: &Vec<u64>, map: &BTreeMap<u64, Range<u64>>) -> Vec<u64>
             moved.push(*seed);
         }
     }
-    moved
+    Ok(moved)
+}
+
+/// [`Solution`] for day 5.
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    type Input = Almanac;
+
+    fn parse(input: &str) -> Result<Almanac> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(almanac: &Almanac) -> Result<Answer> {
+        Ok(lowest_location(almanac)?.into())
+    }
+
+    fn part2(almanac: &Almanac) -> Result<Answer> {
+        Ok(lowest_range_location(almanac)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-    #[test]
-    fn day5_part1_small_test() {
-        let test = String::from(
-            r"seeds: 79 14 55 13
-
-    seed-to-soil map:
-    50 98 2
-    52 50 48
-
-    soil-to-fertilizer map:
-    0 15 37
-    37 52 2
-    39 0 15
-
-    fertilizer-to-water map:
-    49 53 8
-    0 11 42
-    42 0 7
-    57 7 4
-
-    water-to-light map:
-    88 18 7
-    18 25 70
-
-    light-to-temperature map:
-    45 77 23
-    81 45 19
-    68 64 13
-
-    temperature-to-humidity map:
-    0 69 1
-    1 0 69
-
-    humidity-to-location map:
-    60 56 37
-    56 93 4",
-        );
-        assert_eq!(day5_part1(test), 35);
-    }
-
+    use insta::assert_debug_snapshot;
     #[test]
     fn day5_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day5/input.txt")?;
+        let input = test_input(5)?;
 
-        let r = day5_part1(input);
+        let r = day5_part1(input)?;
         println!("{}", r);
         assert_eq!(r, 346433842);
         Ok(())
     }
     #[test]
-    fn day5_part2_small_test() {
-        let test = String::from(
-            r"seeds: 79 14 55 13
-
-seed-to-soil map:
-50 98 2
-52 50 48
-
-soil-to-fertilizer map:
-0 15 37
-37 52 2
-39 0 15
-
-fertilizer-to-water map:
-49 53 8
-0 11 42
-42 0 7
-57 7 4
-
-water-to-light map:
-88 18 7
-18 25 70
-
-light-to-temperature map:
-45 77 23
-81 45 19
-68 64 13
-
-temperature-to-humidity map:
-0 69 1
-1 0 69
-
-humidity-to-location map:
-60 56 37
-56 93 4
-",
+    fn day5_parse_error() {
+        let error = parse("seeds: 79 x").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (1, 11, "x")
+        );
+        let error = day5_part2(String::from("seeds: 79 14 55")).unwrap_err();
+        assert_eq!(error.downcast_ref::<ParseError>().unwrap().line, 1);
+        let error = parse("").unwrap_err();
+        assert_eq!(error, ParseError::truncated(5, 1, "expected `seeds:`"));
+        let error = parse("seeds: 5 3\n\na-to-b map:\n18446744073709551615 0 10").unwrap_err();
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (4, "range is too large")
         );
-        assert_eq!(day5_part2(test), 46);
+    }
+
+    #[test]
+    fn day5_overflow() {
+        let input = String::from("seeds: 18446744073709551615 1");
+        assert_eq!(day5_part1(input.clone()).unwrap(), 1);
+        let error = day5_part2(input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the seed range 18446744073709551615 1 overflows u64"
+        );
+        // the parser rejects such a map, the public fields do not
+        let almanac = Almanac {
+            seeds: vec![5, 3],
+            maps: vec![Map::from([(u64::MAX, 0..10)])],
+        };
+        assert!(lowest_location(&almanac).is_err());
+        assert!(lowest_range_location(&almanac).is_err());
+        let empty = Almanac {
+            seeds: vec![],
+            maps: vec![],
+        };
+        assert!(lowest_location(&empty).is_err());
     }
 
     #[test]
     fn day5_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day5/input.txt")?;
-        let r = day5_part2(input);
+        let input = test_input(5)?;
+        let r = day5_part2(input)?;
         println!("{}", r);
         assert_eq!(r, 60294664);
         Ok(())
     }
+
+    #[test]
+    fn day5_snapshot() -> Result<()> {
+        assert_debug_snapshot!("almanac", parse(&fixture(5, "example")?)?);
+        Ok(())
+    }
 }
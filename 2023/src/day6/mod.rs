@@ -1,89 +1,169 @@
-use roots::find_roots_quadratic;
-use roots::Roots;
+//! Day 6: Wait For It.
 
-fn run(puzzle: &Vec<Vec<usize>>) -> usize {
-    let times = puzzle.get(0).expect("Wrong input format");
-    let distances = puzzle.get(1).expect("Wrong input format");
-    times
+use anyhow::{Context, Result};
+use nom::character::complete::digit1;
+use tracing::debug;
+
+use crate::{
+    error::{Line, ParseError},
+    math::quadratic_positive,
+    parsing::{key_values, list, parse_line},
+    solution::{Answer, Solution},
+};
+
+/// The time and the record distance of every race, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Races {
+    /// The numbers of the `Time:` row.
+    pub times: Vec<usize>,
+    /// The numbers of the `Distance:` row, as many as the times.
+    pub distances: Vec<usize>,
+}
+
+/// Multiplies, over the races, the number of hold times that beat the
+/// record. Fails when a race or the product overflows.
+pub fn ways(races: &Races) -> Result<usize> {
+    races
+        .times
         .iter()
-        .zip(distances.iter())
-        .map(|(time, distance)| {
-            let two_roots = find_roots_quadratic(-1f64, *time as f64, -(*distance as f64));
-            let ways: usize = match two_roots {
-                Roots::Two([lower, upper]) => {
-                    let lower_rounded = lower.floor() as usize;
-                    let upper_rounded = upper.ceil() as usize;
-                    upper_rounded.saturating_sub(lower_rounded).saturating_sub(1)
-                },
-                _ => 0,
-            };
-            ways
+        .zip(races.distances.iter())
+        .try_fold(1usize, |product, (time, distance)| {
+            // holding the button h ms goes h * (time - h) mm: -h² + time·h - distance > 0
+            let holds = quadratic_positive(-1, *time as i128, -(*distance as i128))
+                .with_context(|| format!("the race of {time} ms overflows"))?;
+            let ways = holds.map_or(Some(0), |holds| {
+                usize::try_from(holds.end() - holds.start() + 1).ok()
+            });
+            debug!(time, distance, ways, "race");
+            ways.and_then(|ways| product.checked_mul(ways))
+                .context("the product of the ways overflows usize")
         })
-        .product::<usize>()
 }
-fn day6_part1(input: String) -> usize {
-    let puzzle = input
-        .lines()
-        .map(|line| {
-            line.split(" ")
-                .filter_map(|n| n.parse::<usize>().ok())
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<Vec<usize>>>();
-    run(&puzzle)
+
+/// Parses the `Time:` and `Distance:` rows, with `kerning` each row is a
+/// single number.
+pub fn parse(input: &str, kerning: bool) -> Result<Races, ParseError> {
+    let mut lines = Line::lines(6, input);
+    let mut puzzle: Vec<Vec<usize>> = vec![];
+    for (number, label) in [(1, "Time"), (2, "Distance")] {
+        let line = lines
+            .next()
+            .ok_or_else(|| ParseError::truncated(6, number, format!("expected `{label}:`")))?;
+        let tokens = parse_line(
+            &line,
+            &format!("`{label}:` and numbers"),
+            key_values(label, list(digit1)),
+        )?;
+        let row = if kerning {
+            vec![line.parse::<usize>(&tokens.concat(), "a number")?]
+        } else {
+            tokens
+                .iter()
+                .map(|n| line.parse::<usize>(n, "a number"))
+                .collect::<Result<Vec<usize>, ParseError>>()?
+        };
+        if puzzle.first().is_some_and(|times| times.len() != row.len()) {
+            return Err(line.error(line.text, "expected a distance for every time"));
+        }
+        puzzle.push(row);
+    }
+    if let Some(line) = lines.next() {
+        return Err(line.error(line.text, "expected no line after `Distance:`"));
+    }
+    let distances = puzzle.pop().expect("both rows are parsed");
+    let times = puzzle.pop().expect("both rows are parsed");
+    Ok(Races { times, distances })
 }
 
-fn day6_part2(input: String) -> usize {
-    let puzzle = input
-        .lines()
-        .map(|line| {
-            line.replace(" ", "")
-                .split(":")
-                .filter_map(|n| n.parse::<usize>().ok())
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<Vec<usize>>>();
-    run(&puzzle)
+/// Product of the ways to beat the record of every race.
+pub fn day6_part1(input: String) -> Result<usize> {
+    ways(&parse(&input, false)?)
+}
+
+/// Ways to beat the record when the spaces are removed, one single race.
+pub fn day6_part2(input: String) -> Result<usize> {
+    ways(&parse(&input, true)?)
+}
+
+/// [`Solution`] for day 6.
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    /// The races as written, and the single race read without the spaces.
+    type Input = (Races, Races);
+
+    fn parse(input: &str) -> Result<(Races, Races)> {
+        Ok((parse(input, false)?, parse(input, true)?))
+    }
+
+    fn part1((races, _): &(Races, Races)) -> Result<Answer> {
+        Ok(ways(races)?.into())
+    }
+
+    fn part2((_, race): &(Races, Races)) -> Result<Answer> {
+        Ok(ways(race)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-    #[test]
-    fn day6_part1_small_test() {
-        let test = String::from(
-            r"Time:      7  15   30
-Distance:  9  40  200",
-        );
-        assert_eq!(day6_part1(test), 288);
-    }
-
+    use insta::assert_debug_snapshot;
     #[test]
     fn day6_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day6/input.txt")?;
+        let input = test_input(6)?;
 
-        let r = day6_part1(input);
+        let r = day6_part1(input)?;
         println!("{}", r);
         assert_eq!(r, 861300);
         Ok(())
     }
     #[test]
-    fn day6_part2_small_test() {
-        let test = String::from(
-            r"Time:      7  15   30
-    Distance:  9  40  200",
+    fn day6_part2_test() -> Result<()> {
+        let input = test_input(6)?;
+        let r = day6_part2(input)?;
+        println!("{}", r);
+        assert_eq!(r, 28101347);
+        Ok(())
+    }
+
+    #[test]
+    fn day6_part2_adversarial_test() -> Result<()> {
+        // only holding 2^31 ms beats the record, past what an f64 can tell apart
+        let test = String::from("Time: 4294967296\nDistance: 4611686018427387903");
+        assert_eq!(day6_part2(test)?, 1);
+        let test = String::from("Time: 18446744073709551615\nDistance: 1");
+        let error = day6_part2(test).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the race of 18446744073709551615 ms overflows"
         );
-        assert_eq!(day6_part2(test), 71503);
+        Ok(())
     }
 
     #[test]
-    fn day6_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day6/input.txt")?;
-        let r = day6_part2(input);
-        println!("{}", r);
-        assert_eq!(r, 28101347);
+    fn day6_trailing_line() {
+        let error = parse("Time: 7 15\nDistance: 9 40\nTime: 30", false);
+        assert_eq!(
+            error,
+            Err(ParseError::new(
+                6,
+                3,
+                1,
+                "Time: 30",
+                "expected no line after `Distance:`"
+            ))
+        );
+    }
+
+    #[test]
+    fn day6_snapshot() -> Result<()> {
+        let input = fixture(6, "example")?;
+        assert_debug_snapshot!("races", parse(&input, false)?);
+        assert_debug_snapshot!("kerned_race", parse(&input, true)?);
         Ok(())
     }
 }
@@ -1,7 +1,26 @@
-use std::{cmp::Ordering, collections::BTreeMap};
+//! Day 7: Camel Cards.
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum HandType {
+use std::{collections::BTreeMap, io::BufRead};
+
+use anyhow::{bail, Context, Result};
+use nom::{
+    character::complete::{alphanumeric1, space1},
+    sequence::separated_pair,
+};
+use tracing::{debug, trace};
+
+use crate::{
+    error::{InputError, Line, ParseError},
+    parsing::{parse_line, unsigned},
+    solution::{Answer, Solution},
+};
+
+/// Every card label, weakest first.
+const LABELS: &str = "23456789TJQKA";
+
+/// The kind of a hand, the discriminant is its rank.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HandType {
     /// Five of a kind, where all five cards have the same label: AAAAA
     FiveOfAKind = 7,
     /// Four of a kind, where four cards have the same label and one card has a different label: AA8AA
@@ -18,16 +37,31 @@ enum HandType {
     HighCard = 1,
 }
 
-#[derive(Debug)]
-struct Hand {
-    cards: Vec<char>,
-    bid: usize,
-    hand_type: HandType,
+/// A hand of cards and its bid: `32T3K 765`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hand {
+    /// The card labels in order.
+    pub cards: Vec<char>,
+    /// The bid of the hand.
+    pub bid: usize,
+    /// The type of the hand.
+    pub hand_type: HandType,
 }
 
 impl Hand {
-    fn new(input: &str, part2: bool) -> Hand {
-        let (cards, bid) = input.split_once(" ").expect("Wrong line format");
+    /// Parses a hand line, with `part2` the `J` cards are jokers.
+    pub fn new(line: &Line<'_>, part2: bool) -> Result<Hand, ParseError> {
+        let (cards, bid) = parse_line(
+            line,
+            "`<cards> <bid>`",
+            separated_pair(alphanumeric1, space1, unsigned),
+        )?;
+        if cards.chars().count() != 5 {
+            return Err(line.error(cards, "expected five cards"));
+        }
+        if let Some((i, _)) = cards.char_indices().find(|(_, c)| !LABELS.contains(*c)) {
+            return Err(line.error(&cards[i..], format!("expected a card in {LABELS}")));
+        }
         let mut cards_count: BTreeMap<char, usize> =
             cards
                 .chars()
@@ -56,17 +90,77 @@ impl Hand {
             (5, _) => HandType::FiveOfAKind,
             _ => unreachable!(),
         };
+        trace!(line = line.number, ?hand_type, "hand");
 
-        Hand {
+        Ok(Hand {
             cards: cards.chars().collect(),
-            bid: bid.parse().expect("Wrong bid format"),
+            bid,
             hand_type,
-        }
+        })
+    }
+}
+
+/// Parses the hands, with `part2` the `J` cards are jokers.
+pub fn parse(input: &str, part2: bool) -> Result<Vec<Hand>, ParseError> {
+    Line::lines(7, input)
+        .map(|line| Hand::new(&line, part2))
+        .collect()
+}
+
+/// Reads the hands of `input`, see [`parse`].
+fn read(input: impl BufRead, part2: bool) -> Result<Vec<Hand>, InputError> {
+    // the hands are ranked against each other, only their text is dropped
+    let mut hands = vec![];
+    Line::read_each(7, input, |line| {
+        hands.push(Hand::new(&line, part2)?);
+        Ok(())
+    })?;
+    Ok(hands)
+}
+
+/// The strength of every card label, `J` is the weakest with `part2`.
+fn strengths(part2: bool) -> BTreeMap<char, i32> {
+    if part2 {
+        BTreeMap::from([
+            ('J', 1),
+            ('2', 2),
+            ('3', 3),
+            ('4', 4),
+            ('5', 5),
+            ('6', 6),
+            ('7', 7),
+            ('8', 8),
+            ('9', 9),
+            ('T', 10),
+            ('Q', 12),
+            ('K', 13),
+            ('A', 14),
+        ])
+    } else {
+        BTreeMap::from([
+            ('2', 2),
+            ('3', 3),
+            ('4', 4),
+            ('5', 5),
+            ('6', 6),
+            ('7', 7),
+            ('8', 8),
+            ('9', 9),
+            ('T', 10),
+            ('J', 11),
+            ('Q', 12),
+            ('K', 13),
+            ('A', 14),
+        ])
     }
 }
 
-fn run(input: String, cards_strenght: &BTreeMap<char, i32>, part2: bool) -> usize {
-    let mut hands: Vec<Hand> = input.lines().map(|line| Hand::new(line, part2)).collect();
+/// Total winnings of `hands`, parsed with the same `part2` as given here.
+/// Fails when they do not fit in `usize`.
+pub fn winnings(hands: &[Hand], part2: bool) -> Result<usize> {
+    let cards_strenght = strengths(part2);
+    let mut hands = hands.iter().collect::<Vec<&Hand>>();
+    debug!(hands = hands.len(), "hands read");
     hands.sort_by(|a, b| {
         if a.hand_type == b.hand_type {
             for (a_char, b_char) in a.cards.iter().zip(b.cards.iter()) {
@@ -86,98 +180,100 @@ fn run(input: String, cards_strenght: &BTreeMap<char, i32>, part2: bool) -> usiz
             .expect("Invalid rank")
     });
 
-    let mut starting_rank = 0;
-    hands
-        .iter()
-        .map(|hand| {
-            starting_rank += 1;
-            hand.bid * starting_rank
+    (1..)
+        .zip(hands)
+        .try_fold(0usize, |sum, (rank, hand)| {
+            hand.bid
+                .checked_mul(rank)
+                .and_then(|winning| sum.checked_add(winning))
         })
-        .sum()
+        .context("the winnings overflow usize")
+}
+
+/// Total winnings of the hands.
+pub fn day7_part1(input: impl BufRead) -> Result<usize> {
+    winnings(&read(input, false)?, false)
 }
 
-fn day7_part1(input: String) -> usize {
-    let cards_strenght = BTreeMap::from([
-        ('2', 2),
-        ('3', 3),
-        ('4', 4),
-        ('5', 5),
-        ('6', 6),
-        ('7', 7),
-        ('8', 8),
-        ('9', 9),
-        ('T', 10),
-        ('J', 11),
-        ('Q', 12),
-        ('K', 13),
-        ('A', 14),
-    ]);
-    run(input, &cards_strenght, false)
+/// Total winnings when `J` cards are jokers.
+pub fn day7_part2(input: impl BufRead) -> Result<usize> {
+    winnings(&read(input, true)?, true)
 }
 
-fn day7_part2(input: String) -> usize {
-    let cards_strenght = BTreeMap::from([
-        ('J', 1),
-        ('2', 2),
-        ('3', 3),
-        ('4', 4),
-        ('5', 5),
-        ('6', 6),
-        ('7', 7),
-        ('8', 8),
-        ('9', 9),
-        ('T', 10),
-        ('Q', 12),
-        ('K', 13),
-        ('A', 14),
-    ]);
-    run(input, &cards_strenght, true)
+/// [`Solution`] for day 7.
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    /// The hands, and the hands where the `J` cards are jokers.
+    type Input = (Vec<Hand>, Vec<Hand>);
+
+    fn parse(input: &str) -> Result<(Vec<Hand>, Vec<Hand>)> {
+        Ok((parse(input, false)?, parse(input, true)?))
+    }
+
+    fn part1((hands, _): &(Vec<Hand>, Vec<Hand>)) -> Result<Answer> {
+        Ok(winnings(hands, false)?.into())
+    }
+
+    fn part2((_, hands): &(Vec<Hand>, Vec<Hand>)) -> Result<Answer> {
+        Ok(winnings(hands, true)?.into())
+    }
+
+    fn solve_reader(reader: &mut dyn BufRead, part: u8) -> Result<Answer> {
+        match part {
+            1 => Ok(day7_part1(reader)?.into()),
+            2 => Ok(day7_part2(reader)?.into()),
+            _ => bail!("part {part} does not exist"),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-    #[test]
-    fn day7_part1_small_test() {
-        let test = String::from(
-            r"32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483",
-        );
-        assert_eq!(day7_part1(test), 6440);
-    }
-
+    use insta::assert_debug_snapshot;
     #[test]
     fn day7_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day7/input.txt")?;
+        let input = test_input(7)?;
 
-        let r = day7_part1(input);
+        let r = day7_part1(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 253910319);
         Ok(())
     }
     #[test]
-    fn day7_part2_small_test() {
-        let test = String::from(
-            r"32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483",
-        );
-        assert_eq!(day7_part2(test), 5905);
+    fn day7_parse_error() {
+        let error = day7_part1("32T3K 765\n32T3X 684".as_bytes()).unwrap_err();
+        let Some(InputError::Parse(error)) = error.downcast_ref::<InputError>() else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 5, "X"));
+    }
+
+    #[test]
+    fn day7_overflow() {
+        let error = day7_part1("AAAAA 18446744073709551615\n22222 2".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "the winnings overflow usize");
     }
 
     #[test]
     fn day7_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day7/input.txt")?;
-        let r = day7_part2(input);
+        let input = test_input(7)?;
+        let r = day7_part2(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 254083736);
         Ok(())
     }
+
+    #[test]
+    fn day7_snapshot() -> Result<()> {
+        let input = fixture(7, "example")?;
+        for (name, part2) in [("hands", false), ("hands_with_jokers", true)] {
+            assert_debug_snapshot!(name, parse(&input, part2)?);
+        }
+        Ok(())
+    }
 }
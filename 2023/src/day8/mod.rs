@@ -1,147 +1,367 @@
+//! Day 8: Haunted Wasteland.
+
 use std::collections::BTreeMap;
 
-use gcd::Gcd;
+use anyhow::{bail, Context, Result};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use tracing::{debug, debug_span, Span};
 
-fn parse_input<'a>(input: &'a String) -> (Vec<&'a str>, BTreeMap<&'a str, (&'a str, &'a str)>) {
-    let mut lines = input.lines();
-    let directions = lines
-        .next()
-        .expect("Wrong input format")
-        .split("")
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<&str>>();
-    lines.next();
-    let mapping = lines.fold(BTreeMap::<&str, (&str, &str)>::new(), |mut acc, line| {
-        let (from, mut next) = line.split_once(" = ").expect("Wrong input format");
-        next = next.trim_matches(|c| c == '(' || c == ')');
-        let next = next.split_once(", ").expect("Wrong input format");
-        acc.insert(from, next);
-        acc
-    });
-    (directions, mapping)
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, char},
+    combinator::value,
+    multi::many1,
+    sequence::{delimited, separated_pair},
+};
+
+use crate::{
+    error::{Line, ParseError},
+    math::lcm_all,
+    parsing::parse_line,
+    solution::{Answer, Solution},
+};
+
+/// An `L` or `R` instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// `L`, to the first node of the pair.
+    Left,
+    /// `R`, to the second node of the pair.
+    Right,
+}
+
+impl Direction {
+    /// The node of `pair` this direction goes to.
+    pub fn pick(self, (left, right): &(String, String)) -> &str {
+        match self {
+            Direction::Left => left,
+            Direction::Right => right,
+        }
+    }
 }
 
-fn day8_part1(input: String) -> usize {
-    let (directions, mapping) = parse_input(&input);
-    let mut steps = 0;
-    let mut next = "AAA";
-    loop {
-        for direction in &directions {
-            steps += 1;
-            let current = mapping.get(next).expect("To have the next in the mapping");
-            next = if *direction == "R" {
-                current.1
-            } else {
-                current.0
-            };
-            if next == "ZZZ" {
-                return steps;
+/// The instructions and the `AAA = (BBB, CCC)` nodes of the network.
+pub type Network = (Vec<Direction>, BTreeMap<String, (String, String)>);
+
+/// Parses the instructions line and the node mapping.
+pub fn parse_input(input: &str) -> Result<Network, ParseError> {
+    let mut lines = Line::lines(8, input);
+    let first = lines
+        .next()
+        .ok_or_else(|| ParseError::truncated(8, 1, "expected the directions"))?;
+    let directions = parse_line(
+        &first,
+        "`L` or `R`",
+        many1(alt((
+            value(Direction::Left, char('L')),
+            value(Direction::Right, char('R')),
+        ))),
+    )?;
+    let blank = lines
+        .next()
+        .ok_or_else(|| ParseError::truncated(8, 2, "expected a blank line"))?;
+    if !blank.text.is_empty() {
+        return Err(blank.error(blank.text, "expected a blank line"));
+    }
+    let mut nodes = vec![];
+    let mut mapping = BTreeMap::<&str, (&str, &str)>::new();
+    for line in lines {
+        let (from, next) = parse_line(
+            &line,
+            "`<node> = (<left>, <right>)`",
+            separated_pair(
+                alphanumeric1,
+                tag(" = "),
+                delimited(
+                    char('('),
+                    separated_pair(alphanumeric1, tag(", "), alphanumeric1),
+                    char(')'),
+                ),
+            ),
+        )?;
+        if mapping.insert(from, next).is_some() {
+            return Err(line.error(from, "expected a node not defined yet"));
+        }
+        nodes.push((line, next));
+    }
+    for (line, (left, right)) in nodes {
+        for node in [left, right] {
+            if !mapping.contains_key(node) {
+                return Err(line.error(node, "expected a node of the network"));
             }
         }
     }
+    let mapping = mapping
+        .into_iter()
+        .map(|(from, (left, right))| (from.to_owned(), (left.to_owned(), right.to_owned())))
+        .collect();
+    Ok((directions, mapping))
+}
+
+/// Steps from `AAA` to `ZZZ`.
+pub fn day8_part1(input: String) -> Result<usize> {
+    steps(&parse_input(&input)?)
+}
+
+/// Steps until every node ending in `A` is at a node ending in `Z`.
+pub fn day8_part2(input: String) -> Result<usize> {
+    ghost_steps(&parse_input(&input)?)
+}
+
+/// [`day8_part1`] on the parsed network.
+pub fn steps(network: &Network) -> Result<usize> {
+    let (_, mapping) = network;
+    if !mapping.contains_key("AAA") {
+        bail!("no node `AAA`");
+    }
+    let (steps, _) =
+        first_end(network, "AAA", 0, |node| node == "ZZZ").context("`AAA` never reaches `ZZZ`")?;
+    debug!(steps, "reached ZZZ");
+    Ok(steps)
 }
 
-fn day8_part2(input: String) -> usize {
-    let (directions, mapping) = parse_input(&input);
+/// [`day8_part2`] on the parsed network.
+pub fn ghost_steps(network: &Network) -> Result<usize> {
+    let (_, mapping) = network;
     let nexts = mapping
         .keys()
         .filter(|k| k.ends_with("A"))
-        .cloned()
+        .map(String::as_str)
         .collect::<Vec<&str>>();
-    let mut cycles = nexts
+    // the rayon threads do not inherit the current span
+    let parent = Span::current();
+    let cycles = nexts
         .into_par_iter()
         .map(|start| {
-            let mut next = start;
-            let mut steps = 0;
-            loop {
-                for direction in &directions {
-                    steps += 1;
-                    let current = mapping.get(next).expect("To have the next in the mapping");
-                    next = if *direction == "R" {
-                        current.1
-                    } else {
-                        current.0
-                    };
-                    if next.ends_with("Z") {
-                        return steps;
-                    }
-                }
-            }
+            let _span = debug_span!(parent: &parent, "ghost", start).entered();
+            let (steps, end) = first_end(network, start, 0, |node| node.ends_with('Z'))
+                .with_context(|| format!("`{start}` never reaches a node ending in `Z`"))?;
+            debug!(end, steps, "cycle found");
+            Ok(steps as u64)
         })
-        .collect::<Vec<usize>>();
+        .collect::<Result<Vec<u64>>>()?;
 
-    let lcm = cycles.pop().expect("To have a cycle");
-    cycles
-        .into_iter()
-        .fold(lcm, |acc, cycle| (acc * (cycle)) / acc.gcd(cycle))
+    if cycles.is_empty() {
+        bail!("no node ends in `A`");
+    }
+    let lcm = lcm_all(&cycles).context("the steps overflow 64 bits")?;
+    usize::try_from(lcm).context("the steps overflow usize")
+}
+
+/// Steps from `start` to the first node that is an `end`, following the
+/// directions from the `offset`th one, `None` when it never gets there.
+fn first_end<'a>(
+    (directions, mapping): &'a Network,
+    start: &'a str,
+    offset: usize,
+    end: impl Fn(&str) -> bool,
+) -> Option<(usize, &'a str)> {
+    let mut next = start;
+    // past this many steps a node and direction pair has repeated
+    for steps in 1..=directions.len() * mapping.len() {
+        next = directions[(offset + steps - 1) % directions.len()].pick(&mapping[next]);
+        if end(next) {
+            return Some((steps, next));
+        }
+    }
+    None
+}
+
+/// Checks that the ghost of every node ending in `A` reaches a node ending in
+/// `Z` after a whole number of passes over the directions, then comes back to
+/// it after as many steps again, so that part 2 is the LCM of these steps.
+pub fn validate(input: &str) -> Result<Vec<ParseError>, ParseError> {
+    let network = parse_input(input)?;
+    let (directions, mapping) = &network;
+    let starts = mapping
+        .keys()
+        .filter(|node| node.ends_with('A'))
+        .map(String::as_str)
+        .collect::<Vec<&str>>();
+    if starts.is_empty() {
+        let text = input.lines().nth(2).unwrap_or_default();
+        return Ok(vec![ParseError::new(
+            8,
+            3,
+            1,
+            text,
+            "expected a node ending in `A`",
+        )]);
+    }
+    let mut violations = vec![];
+    for start in starts {
+        let error = |message: String| {
+            let node = format!("{start} =");
+            let line = Line::lines(8, input)
+                .skip(2)
+                .find(|line| line.text.starts_with(&node))
+                .expect("every node has a line");
+            line.error(&line.text[..start.len()], message)
+        };
+        let Some((steps, end)) = first_end(&network, start, 0, |node| node.ends_with('Z')) else {
+            violations.push(error("expected to reach a node ending in `Z`".to_owned()));
+            continue;
+        };
+        if steps % directions.len() != 0 {
+            // the loop would start again from another direction
+            violations.push(error(format!(
+                "expected to reach `{end}` after a multiple of the {} directions, reaches it after {steps} steps",
+                directions.len()
+            )));
+            continue;
+        }
+        let message = match first_end(&network, end, 0, |node| node.ends_with('Z')) {
+            Some((again, next)) if again == steps && next == end => continue,
+            Some((again, next)) => format!("reaches `{next}` {again} steps after"),
+            None => "never reaches a node ending in `Z` after".to_owned(),
+        };
+        violations.push(error(format!(
+            "expected to loop on `{end}` every {steps} steps, {message}"
+        )));
+    }
+    Ok(violations)
+}
+
+/// [`Solution`] for day 8.
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+    type Input = Network;
+
+    fn parse(input: &str) -> Result<Network> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(network: &Network) -> Result<Answer> {
+        Ok(steps(network)?.into())
+    }
+
+    fn part2(network: &Network) -> Result<Answer> {
+        Ok(ghost_steps(network)?.into())
+    }
+
+    fn validate(input: &str) -> Result<Vec<ParseError>> {
+        Ok(validate(input)?)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
+    use insta::assert_debug_snapshot;
+    #[test]
+    fn day8_part1_test() -> Result<()> {
+        let input = test_input(8)?;
+
+        let r = day8_part1(input)?;
+        println!("{}", r);
+        assert_eq!(r, 14893);
+        Ok(())
+    }
     #[test]
-    fn day8_part1_small_test() {
-        let test = String::from(
-            r"RL
-
-AAA = (BBB, CCC)
-BBB = (DDD, EEE)
-CCC = (ZZZ, GGG)
-DDD = (DDD, DDD)
-EEE = (EEE, EEE)
-GGG = (GGG, GGG)
-ZZZ = (ZZZ, ZZZ)",
+    fn day8_parse_error() {
+        let error = parse_input("RL\n\nAAA = (BBB, AAA)").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (3, 8, "BBB")
         );
-        assert_eq!(day8_part1(test), 2);
     }
 
     #[test]
-    fn day8_part1_small_test_2() {
-        let test = String::from(
-            r"LLR
-
-AAA = (BBB, BBB)
-BBB = (AAA, ZZZ)
-ZZZ = (ZZZ, ZZZ)",
+    fn day8_layout_errors() {
+        let error = parse_input("L\nBBB = (AAA, ZZZ)\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)");
+        assert_eq!(
+            error,
+            Err(ParseError::new(
+                8,
+                2,
+                1,
+                "BBB = (AAA, ZZZ)",
+                "expected a blank line"
+            ))
+        );
+        let error = parse_input("L");
+        assert_eq!(
+            error,
+            Err(ParseError::truncated(8, 2, "expected a blank line"))
+        );
+        let error = parse_input("L\n\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\nAAA = (AAA, AAA)");
+        assert_eq!(
+            error,
+            Err(ParseError::new(
+                8,
+                5,
+                1,
+                "AAA",
+                "expected a node not defined yet"
+            ))
         );
-        assert_eq!(day8_part1(test), 6);
     }
 
     #[test]
-    fn day8_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day8/input.txt")?;
-
-        let r = day8_part1(input);
-        println!("{}", r);
-        assert_eq!(r, 14893);
+    fn day8_validate_test() -> Result<()> {
+        assert_eq!(validate(&test_input(8)?)?, vec![]);
+        // `22A` gets to `22Z` in 2 steps but then back to it in 3
+        let input = "LR\n\n11A = (11B, XXX)\n11B = (XXX, 11Z)\n11Z = (11B, XXX)\n\
+                     22A = (22B, XXX)\n22B = (XXX, 22Z)\n22Z = (22C, XXX)\n22C = (XXX, 22D)\n\
+                     22D = (22Z, XXX)\nXXX = (XXX, XXX)";
+        let violations = validate(input)?;
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            (violations[0].line, violations[0].text.as_str()),
+            (6, "22A")
+        );
+        assert_eq!(
+            violations[0].message,
+            "expected to loop on `22Z` every 2 steps, reaches `22Z` 3 steps after"
+        );
+        // `11A` gets back to `11Z` with an `R` but leaves it with the next `L`
+        let input = "LR\n\n11AB = (XXX, XXX)\n11A = (11Z, XXX)\n11Z = (XXX, 11Z)\n\
+                     22A = (22B, 22B)\n22B = (22Z, 22Z)\n22Z = (22B, 22B)\nXXX = (XXX, XXX)";
+        let violations = validate(input)?;
+        assert_eq!(
+            violations,
+            vec![ParseError::new(
+                8,
+                4,
+                1,
+                "11A",
+                "expected to reach `11Z` after a multiple of the 2 directions, reaches it after 1 steps"
+            )]
+        );
         Ok(())
     }
+
     #[test]
-    fn day8_part2_small_test() {
-        let test = String::from(
-            r"LR
-
-11A = (11B, XXX)
-11B = (XXX, 11Z)
-11Z = (11B, XXX)
-22A = (22B, XXX)
-22B = (22C, 22C)
-22C = (22Z, 22Z)
-22Z = (22B, 22B)
-XXX = (XXX, XXX)",
+    fn day8_unreachable_end() -> Result<()> {
+        let network = parse_input("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)")?;
+        assert_eq!(
+            steps(&network).unwrap_err().to_string(),
+            "`AAA` never reaches `ZZZ`"
         );
-        assert_eq!(day8_part2(test), 6);
+        assert_eq!(
+            ghost_steps(&network).unwrap_err().to_string(),
+            "`AAA` never reaches a node ending in `Z`"
+        );
+        Ok(())
     }
 
     #[test]
     fn day8_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day8/input.txt")?;
-        let r = day8_part2(input);
+        let input = test_input(8)?;
+        let r = day8_part2(input)?;
         println!("{}", r);
         assert_eq!(r, 10241191004509);
         Ok(())
     }
+
+    #[test]
+    fn day8_snapshot() -> Result<()> {
+        assert_debug_snapshot!("network", parse_input(&fixture(8, "example3")?)?);
+        Ok(())
+    }
 }
@@ -1,109 +1,212 @@
+//! Day 9: Mirage Maintenance.
+
+use std::io::BufRead;
+
+use anyhow::{bail, Context};
 use itertools::Itertools;
-fn day9_part1(input: String) -> i64 {
-    input
-        .lines()
-        .map(|line| {
-            let current_values = line
-                .split(" ")
-                .filter_map(|values| values.parse::<i64>().ok())
-                .collect::<Vec<i64>>();
-
-            let mut sequences = vec![current_values];
-            loop {
-                let current_history = sequences.last().expect("Invalid sequence");
-                let mut differences = vec![];
-                
-                for (current, next) in current_history.iter().tuple_windows() {
-                    differences.push(*next - *current);
-                }
-                if differences.iter().all(|d| *d == 0) {
-                    break;
-                }
-                sequences.push(differences);
-            }
-            sequences.reverse();
-            sequences.iter().fold(0, |acc, sequence| {
-                acc + sequence.last().expect("Invalid sequence")
-            })
-        })
-        .sum()
+use tracing::trace;
+
+use crate::{
+    error::{InputError, Line, ParseError},
+    math::Overflow,
+    parsing::{parse_line, signed_list},
+    solution::{Answer, Solution},
+};
+
+/// Parses the values of a history line.
+fn history(line: Line<'_>) -> Result<Vec<i64>, ParseError> {
+    parse_line(&line, "a history of numbers", signed_list)
+}
+
+/// The history and its rows of differences, down to the last row that is not
+/// all zeros. Fails when a difference does not fit in `i64`.
+fn pyramid(history: Vec<i64>) -> Result<Vec<Vec<i64>>, Overflow> {
+    let mut sequences = vec![history];
+    loop {
+        let current_history = sequences.last().expect("Invalid sequence");
+        let mut differences = vec![];
+        for (current, next) in current_history.iter().tuple_windows() {
+            differences.push(next.checked_sub(*current).ok_or(Overflow)?);
+        }
+        if differences.iter().all(|d| *d == 0) {
+            break;
+        }
+        sequences.push(differences);
+    }
+    Ok(sequences)
+}
+
+/// Parses the histories, one per line.
+pub fn parse(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    Line::lines(9, input).map(history).collect()
+}
+
+/// The value after the history on line `line_number`.
+fn next(line_number: usize, history: Vec<i64>) -> Result<i64, Overflow> {
+    let mut sequences = pyramid(history)?;
+    sequences.reverse();
+    let next = sequences.iter().try_fold(0i64, |acc, sequence| {
+        acc.checked_add(*sequence.last().expect("Invalid sequence"))
+            .ok_or(Overflow)
+    })?;
+    trace!(
+        line = line_number,
+        depth = sequences.len(),
+        next,
+        "prediction"
+    );
+    Ok(next)
+}
+
+/// The value before the history on line `line_number`.
+fn previous(line_number: usize, history: Vec<i64>) -> Result<i64, Overflow> {
+    let mut sequences = pyramid(history)?;
+    let starting = sequences
+        .pop()
+        .expect("Invalid input")
+        .first()
+        .expect("Invalid sequence")
+        .to_owned();
+
+    sequences.reverse();
+    let previous = sequences.iter().try_fold(starting, |acc, sequence| {
+        sequence
+            .first()
+            .expect("Invalid sequence")
+            .checked_sub(acc)
+            .ok_or(Overflow)
+    })?;
+    trace!(
+        line = line_number,
+        depth = sequences.len(),
+        previous,
+        "prediction"
+    );
+    Ok(previous)
+}
+
+/// Sums `predict` over the lines of `input`, pointing at the line whose
+/// prediction overflows.
+fn sum_predictions(
+    input: impl BufRead,
+    predict: fn(usize, Vec<i64>) -> Result<i64, Overflow>,
+) -> Result<i64, InputError> {
+    Line::sum_each(9, input, |line| {
+        predict(line.number, history(line)?)
+            .map_err(|_| line.error(line.text, "expected the prediction to fit in i64"))
+    })
+}
+
+/// Sums `predict` over the parsed `histories`.
+fn sum_parsed(
+    histories: &[Vec<i64>],
+    predict: fn(usize, Vec<i64>) -> Result<i64, Overflow>,
+) -> anyhow::Result<i64> {
+    (1..).zip(histories).try_fold(0i64, |sum, (line, history)| {
+        let prediction = predict(line, history.clone())
+            .with_context(|| format!("the prediction of line {line} overflows i64"))?;
+        sum.checked_add(prediction)
+            .context("the sum of the predictions overflows i64")
+    })
+}
+
+/// Sum of the next value of every history.
+pub fn day9_part1(input: impl BufRead) -> Result<i64, InputError> {
+    sum_predictions(input, next)
+}
+
+/// Sum of the previous value of every history.
+pub fn day9_part2(input: impl BufRead) -> Result<i64, InputError> {
+    sum_predictions(input, previous)
+}
+
+/// [`day9_part1`] on the parsed histories.
+pub fn next_values(histories: &[Vec<i64>]) -> anyhow::Result<i64> {
+    sum_parsed(histories, next)
+}
+
+/// [`day9_part2`] on the parsed histories.
+pub fn previous_values(histories: &[Vec<i64>]) -> anyhow::Result<i64> {
+    sum_parsed(histories, previous)
 }
 
-fn day9_part2(input: String) -> i64 {
-    input
-        .lines()
-        .map(|line| {
-            let current_values = line
-                .split(" ")
-                .filter_map(|values| values.parse::<i64>().ok())
-                .collect::<Vec<i64>>();
-
-            let mut sequences = vec![current_values];
-            loop {
-                let current_history = sequences.last().expect("Invalid sequence");
-                let mut differences = vec![];
-                for (current, next) in current_history.iter().tuple_windows() {
-                    differences.push(*next - *current);
-                }
-                if differences.iter().all(|d| *d == 0) {
-                    break;
-                }
-                sequences.push(differences);
-            }
-            let starting = sequences
-                .pop()
-                .expect("Invalid input")
-                .first()
-                .expect("Invalid sequence")
-                .to_owned();
-
-            sequences.reverse();
-            sequences.iter().fold(starting, |acc, sequence| {
-                sequence.first().expect("Invalid sequence") - acc
-            })
-        })
-        .sum()
+/// [`Solution`] for day 9.
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    type Input = Vec<Vec<i64>>;
+
+    fn parse(input: &str) -> anyhow::Result<Vec<Vec<i64>>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(histories: &Vec<Vec<i64>>) -> anyhow::Result<Answer> {
+        Ok(next_values(histories)?.into())
+    }
+
+    fn part2(histories: &Vec<Vec<i64>>) -> anyhow::Result<Answer> {
+        Ok(previous_values(histories)?.into())
+    }
+
+    fn solve_reader(reader: &mut dyn BufRead, part: u8) -> anyhow::Result<Answer> {
+        match part {
+            1 => Ok(day9_part1(reader)?.into()),
+            2 => Ok(day9_part2(reader)?.into()),
+            _ => bail!("part {part} does not exist"),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{fixture, test_input};
     use anyhow::{Ok, Result};
-    use std::fs;
-    #[test]
-    fn day9_part1_small_test() {
-        let test = String::from(
-            r"0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45",
-        );
-        assert_eq!(day9_part1(test), 114);
-    }
-
+    use insta::assert_debug_snapshot;
     #[test]
     fn day9_part1_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day9/input.txt")?;
+        let input = test_input(9)?;
 
-        let r = day9_part1(input);
+        let r = day9_part1(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 1684566095);
         Ok(())
     }
     #[test]
-    fn day9_part2_small_test() {
-        let test = String::from(
-            r"0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45",
-        );
-        assert_eq!(day9_part2(test), 2);
-    }
-    #[test]
     fn day9_part2_test() -> Result<()> {
-        let input = fs::read_to_string("./src/day9/input.txt")?;
-        let r = day9_part2(input);
+        let input = test_input(9)?;
+        let r = day9_part2(input.as_bytes())?;
         println!("{}", r);
         assert_eq!(r, 1136);
         Ok(())
     }
+
+    #[test]
+    fn day9_overflow() -> Result<()> {
+        let input = "1 2 3\n9223372036854775807 -9223372036854775808";
+        let Err(InputError::Parse(error)) = day9_part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (2, "expected the prediction to fit in i64")
+        );
+        assert_eq!(
+            next_values(&parse(input)?).unwrap_err().to_string(),
+            "the prediction of line 2 overflows i64"
+        );
+        assert!(previous_values(&parse(input)?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn day9_snapshot() -> Result<()> {
+        let pyramids = parse(&fixture(9, "example")?)?
+            .into_iter()
+            .map(pyramid)
+            .collect::<Result<Vec<Vec<Vec<i64>>>, Overflow>>()?;
+        assert_debug_snapshot!("pyramids", pyramids);
+        Ok(())
+    }
 }
@@ -0,0 +1,596 @@
+//! Seedable generators of valid puzzle inputs, to see how the days behave on
+//! inputs larger than the real ones.
+//!
+//! [`Knobs::of`] sizes an input like the real one of the day, the knobs each
+//! day reads are:
+//!
+//! | day        | knobs                                                  |
+//! |------------|--------------------------------------------------------|
+//! | 1, 2, 4, 7 | `lines`: calibration lines, games, cards or hands      |
+//! | 3, 10      | a `width` × `height` map                               |
+//! | 5          | `lines` seed ranges and `ranges` ranges per map        |
+//! | 6          | `lines` races, at most 4 so part 2 fits 64 bits        |
+//! | 8          | `width` directions and `lines` nodes                   |
+//! | 9          | `lines` histories of `width` values                    |
+//! | 11         | a `width` × `height` image with `lines` galaxies       |
+//! | 12         | `lines` rows of `springs` springs                      |
+//!
+//! The same day, seed and knobs always give the same input.
+
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+use rand::{
+    rngs::StdRng,
+    seq::{IndexedRandom, SliceRandom},
+    Rng, SeedableRng,
+};
+
+/// The sizes of a generated input, see the [module](self) docs for the ones
+/// each day reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Knobs {
+    /// Lines of the inputs that are lists, or galaxies of day 11.
+    pub lines: usize,
+    /// Columns of the maps, values of a day 9 history or day 8 directions.
+    pub width: usize,
+    /// Rows of the maps.
+    pub height: usize,
+    /// Ranges in each day 5 map.
+    pub ranges: usize,
+    /// Springs in each day 12 row.
+    pub springs: usize,
+}
+
+impl Knobs {
+    /// The sizes of the real input of `day`.
+    pub fn of(day: u8) -> Knobs {
+        let knobs = Knobs {
+            lines: 1000,
+            width: 140,
+            height: 140,
+            ranges: 30,
+            springs: 20,
+        };
+        match day {
+            2 | 9 => Knobs {
+                lines: 100,
+                width: 21,
+                ..knobs
+            },
+            4 => Knobs {
+                lines: 200,
+                ..knobs
+            },
+            5 => Knobs { lines: 10, ..knobs },
+            6 => Knobs { lines: 4, ..knobs },
+            8 => Knobs {
+                lines: 750,
+                width: 280,
+                ..knobs
+            },
+            11 => Knobs {
+                lines: 440,
+                ..knobs
+            },
+            _ => knobs,
+        }
+    }
+}
+
+/// Writes the lines of an input.
+type Generator = fn(&mut StdRng, &Knobs) -> Result<Vec<String>>;
+
+/// The generator of `day`, if it has one.
+fn generator(day: u8) -> Option<Generator> {
+    let generator: Generator = match day {
+        1 => |rng, knobs| Ok(calibration(rng, knobs)),
+        2 => |rng, knobs| Ok(games(rng, knobs)),
+        3 => schematic,
+        4 => |rng, knobs| Ok(cards(rng, knobs)),
+        5 => almanac,
+        6 => races,
+        7 => |rng, knobs| Ok(hands(rng, knobs)),
+        8 => network,
+        9 => histories,
+        10 => pipes,
+        11 => image,
+        12 => springs,
+        _ => return None,
+    };
+    Some(generator)
+}
+
+/// Whether [`generate`] can write an input of `day`, a newly scaffolded day
+/// has no generator yet.
+pub fn has_generator(day: u8) -> bool {
+    generator(day).is_some()
+}
+
+/// A valid input of `day`.
+pub fn generate(day: u8, seed: u64, knobs: &Knobs) -> Result<String> {
+    let Some(generator) = generator(day) else {
+        bail!("no generator for day {day}");
+    };
+    let lines = generator(&mut StdRng::seed_from_u64(seed), knobs)?;
+    let mut input = lines.join("\n");
+    input.push('\n');
+    Ok(input)
+}
+
+/// Letters with at least one digit, some of the digits spelled out.
+fn calibration(rng: &mut StdRng, knobs: &Knobs) -> Vec<String> {
+    const SPELLED: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+    (0..knobs.lines)
+        .map(|_| {
+            let mut line = String::new();
+            let digit = rng.random_range(0..5);
+            for i in 0..5 {
+                match rng.random_range(0..4) {
+                    _ if i == digit => line.push(char::from(b'1' + rng.random_range(0..9))),
+                    0 => line.push_str(SPELLED.choose(rng).expect("not empty")),
+                    _ => line.extend(
+                        (0..rng.random_range(1..4))
+                            .map(|_| char::from(b'a' + rng.random_range(0..26))),
+                    ),
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+fn games(rng: &mut StdRng, knobs: &Knobs) -> Vec<String> {
+    (1..=knobs.lines)
+        .map(|id| {
+            let sets = (0..rng.random_range(1..=6))
+                .map(|_| {
+                    let mut colours = vec!["red", "green", "blue"];
+                    colours.shuffle(rng);
+                    colours.truncate(rng.random_range(1..=3));
+                    colours
+                        .iter()
+                        .map(|colour| format!("{} {colour}", rng.random_range(1..=20)))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                })
+                .collect::<Vec<String>>();
+            format!("Game {id}: {}", sets.join("; "))
+        })
+        .collect()
+}
+
+/// Numbers of 1 to 3 digits and symbols scattered on a map of `.`.
+fn schematic(rng: &mut StdRng, knobs: &Knobs) -> Result<Vec<String>> {
+    if knobs.width == 0 || knobs.height == 0 {
+        bail!("day 3 needs a map of at least 1 by 1");
+    }
+    Ok((0..knobs.height)
+        .map(|_| {
+            let mut row = String::new();
+            while row.len() < knobs.width {
+                let left = knobs.width - row.len();
+                match rng.random_range(0..10) {
+                    0..=1 => {
+                        let digits = rng.random_range(1..=3).min(left);
+                        let number = rng.random_range(
+                            10usize.pow(digits as u32 - 1)..10usize.pow(digits as u32),
+                        );
+                        row.push_str(&number.to_string());
+                        // numbers are never side by side
+                        if row.len() < knobs.width {
+                            row.push('.');
+                        }
+                    }
+                    2 => row.push(*b"*#+$/@%=-&".choose(rng).expect("not empty") as char),
+                    _ => row.push('.'),
+                }
+            }
+            row
+        })
+        .collect())
+}
+
+/// Cards of 10 winning numbers and 25 numbers, half of them win nothing
+/// and the others win few enough copies for the total to fit 64 bits.
+fn cards(rng: &mut StdRng, knobs: &Knobs) -> Vec<String> {
+    let format = |numbers: &[usize]| {
+        numbers
+            .iter()
+            .map(|n| format!("{:>2}", n + 1))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+    let mut copies = vec![1u64; knobs.lines];
+    (0..knobs.lines)
+        .map(|i| {
+            let mut wins = if rng.random_bool(0.5) {
+                0
+            } else {
+                rng.random_range(1..=10)
+            };
+            wins = wins.min(knobs.lines - i - 1);
+            if copies[i] > 1 << 40 {
+                wins = 0;
+            }
+            let instances = copies[i];
+            for won in &mut copies[i + 1..=i + wins] {
+                *won += instances;
+            }
+            let numbers = rand::seq::index::sample(rng, 99, 35 - wins).into_vec();
+            let (winning, others) = numbers.split_at(10);
+            let mut elf = winning[..wins].to_vec();
+            elf.extend(others);
+            elf.shuffle(rng);
+            format!("Card {:>3}: {} | {}", i + 1, format(winning), format(&elf))
+        })
+        .collect()
+}
+
+/// Seed ranges and 7 maps, each moving the pieces of the same span around.
+fn almanac(rng: &mut StdRng, knobs: &Knobs) -> Result<Vec<String>> {
+    const SPAN: u64 = 1 << 32;
+    const NAMES: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    if knobs.lines == 0 || knobs.ranges == 0 {
+        bail!("day 5 needs at least a seed range and a range per map");
+    }
+    let seeds = (0..knobs.lines)
+        .map(|_| {
+            let start = rng.random_range(0..SPAN - 1);
+            let length = rng.random_range(1..=(SPAN - start).min(SPAN / 20));
+            format!("{start} {length}")
+        })
+        .collect::<Vec<String>>();
+    let mut lines = vec![format!("seeds: {}", seeds.join(" "))];
+    for names in NAMES.windows(2) {
+        lines.push(String::new());
+        lines.push(format!("{}-to-{} map:", names[0], names[1]));
+        let mut cuts = BTreeSet::from([0, SPAN]);
+        while cuts.len() < knobs.ranges + 1 {
+            cuts.insert(rng.random_range(1..SPAN));
+        }
+        let cuts = cuts.into_iter().collect::<Vec<u64>>();
+        let mut pieces = cuts
+            .windows(2)
+            .map(|w| (w[0], w[1] - w[0]))
+            .collect::<Vec<(u64, u64)>>();
+        pieces.shuffle(rng);
+        let mut destination = 0;
+        for (source, length) in pieces {
+            lines.push(format!("{destination} {source} {length}"));
+            destination += length;
+        }
+    }
+    Ok(lines)
+}
+
+/// Races that can all be won, the record is below the best distance.
+fn races(rng: &mut StdRng, knobs: &Knobs) -> Result<Vec<String>> {
+    if knobs.lines == 0 || knobs.lines > 4 {
+        bail!("day 6 needs 1 to 4 races, part 2 joins their numbers");
+    }
+    let (times, distances): (Vec<String>, Vec<String>) = (0..knobs.lines)
+        .map(|_| {
+            let time = rng.random_range(7..100u64);
+            let best = (time / 2) * (time - time / 2);
+            let distance = rng.random_range(time..best);
+            (format!("{time:>4}"), format!("{distance:>4}"))
+        })
+        .unzip();
+    Ok(vec![
+        format!("Time:    {}", times.join(" ")),
+        format!("Distance:{}", distances.join(" ")),
+    ])
+}
+
+fn hands(rng: &mut StdRng, knobs: &Knobs) -> Vec<String> {
+    const LABELS: &[u8] = b"23456789TJQKA";
+    (0..knobs.lines)
+        .map(|_| {
+            let cards = (0..5)
+                .map(|_| *LABELS.choose(rng).expect("not empty") as char)
+                .collect::<String>();
+            format!("{cards} {}", rng.random_range(1..=1000))
+        })
+        .collect()
+}
+
+/// Loops of nodes, each from a node ending in `A` to one ending in `Z` that
+/// goes back to the second node. The first loop goes from `AAA` to `ZZZ`.
+fn network(rng: &mut StdRng, knobs: &Knobs) -> Result<Vec<String>> {
+    // names end in B to Y, except the starts and ends of the loops, whose
+    // prefixes are neither `AA` nor `ZZ`
+    const NAMES: usize = 26 * 26 * 24;
+    let loops = (knobs.lines / 50).clamp(1, 6);
+    if knobs.width == 0 || knobs.lines < 2 * loops || knobs.lines > NAMES {
+        bail!("day 8 needs some directions and 2 to {NAMES} nodes");
+    }
+    let letter = |n: usize| char::from(b'A' + n as u8);
+    let mut names = rand::seq::index::sample(rng, NAMES, knobs.lines - 2 * loops)
+        .into_iter()
+        .map(|n| {
+            format!(
+                "{}{}{}",
+                letter(n / 624),
+                letter(n / 24 % 26),
+                letter(n % 24 + 1)
+            )
+        })
+        .collect::<Vec<String>>();
+    let mut prefixes = rand::seq::index::sample(rng, 26 * 26 - 2, loops - 1)
+        .into_iter()
+        .map(|n| format!("{}{}", letter((n + 1) / 26), letter((n + 1) % 26)))
+        .collect::<Vec<String>>();
+    prefixes.insert(0, "AA".to_owned());
+
+    let directions = (0..knobs.width)
+        .map(|_| if rng.random_bool(0.5) { 'L' } else { 'R' })
+        .collect::<String>();
+    let mut lines = vec![directions, String::new()];
+    let inner = names.len() / loops;
+    for (i, prefix) in prefixes.iter().enumerate() {
+        let size = if i == 0 {
+            names.len() - inner * (loops - 1)
+        } else {
+            inner
+        };
+        let start = if i == 0 {
+            "AAA".to_owned()
+        } else {
+            format!("{prefix}A")
+        };
+        let end = if i == 0 {
+            "ZZZ".to_owned()
+        } else {
+            format!("{prefix}Z")
+        };
+        let mut nodes = vec![start];
+        nodes.extend(names.drain(..size));
+        nodes.push(end);
+        for (j, node) in nodes.iter().enumerate() {
+            let next = &nodes[if j + 1 == nodes.len() { 1 } else { j + 1 }];
+            lines.push(format!("{node} = ({next}, {next})"));
+        }
+    }
+    lines[2..].shuffle(rng);
+    Ok(lines)
+}
+
+/// Values of polynomials of degree up to 6.
+fn histories(rng: &mut StdRng, knobs: &Knobs) -> Result<Vec<String>> {
+    if knobs.width < 2 {
+        bail!("day 9 needs at least 2 values per history");
+    }
+    (0..knobs.lines)
+        .map(|_| {
+            let coefficients = (0..rng.random_range(1..=7))
+                .map(|_| rng.random_range(-5i128..=5))
+                .collect::<Vec<i128>>();
+            let values = (0..knobs.width as i128)
+                .map(|x| {
+                    let value = coefficients
+                        .iter()
+                        .rev()
+                        .fold(0i128, |acc, c| acc.saturating_mul(x).saturating_add(*c));
+                    // the differences must fit 64 bits too
+                    if value.abs() >= 1 << 50 {
+                        bail!("day 9 histories of {} values overflow", knobs.width);
+                    }
+                    Ok(value.to_string())
+                })
+                .collect::<Result<Vec<String>>>()?;
+            Ok(values.join(" "))
+        })
+        .collect()
+}
+
+/// A loop around a histogram, its top left corner is the start, and junk
+/// pipes everywhere else.
+fn pipes(rng: &mut StdRng, knobs: &Knobs) -> Result<Vec<String>> {
+    let (width, height) = (knobs.width, knobs.height);
+    if width < 5 || height < 4 {
+        bail!("day 10 needs a map of at least 5 by 4");
+    }
+    let right = width - 2;
+    // bottom row of the loop at every column
+    let mut bottom = vec![0; width];
+    bottom[1] = rng.random_range(2..height - 1);
+    for column in 2..right {
+        bottom[column] = bottom[column - 1]
+            .saturating_add_signed(rng.random_range(-2i64..=2) as isize)
+            .clamp(2, height - 2);
+    }
+    bottom[right] = bottom[right - 1];
+
+    let mut path = (1..=right)
+        .map(|column| (1, column))
+        .collect::<Vec<(usize, usize)>>();
+    path.extend((2..=bottom[right]).map(|row| (row, right)));
+    for column in (2..=right).rev() {
+        let (from, to) = (bottom[column], bottom[column - 1]);
+        if to > from {
+            path.extend((from + 1..=to).map(|row| (row, column)));
+        } else {
+            path.extend((to..from).rev().map(|row| (row, column)));
+        }
+        path.push((to, column - 1));
+    }
+    path.extend((2..bottom[1]).rev().map(|row| (row, 1)));
+
+    let mut map = (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| *b"|-LJ7F..".choose(rng).expect("not empty") as char)
+                .collect::<Vec<char>>()
+        })
+        .collect::<Vec<Vec<char>>>();
+    // nothing but the loop leaves the start
+    map[0][1] = '.';
+    map[1][0] = '.';
+    for (i, &(row, column)) in path.iter().enumerate() {
+        let before = path[(i + path.len() - 1) % path.len()];
+        let after = path[(i + 1) % path.len()];
+        let towards = |(r, c): (usize, usize)| match (r.cmp(&row), c.cmp(&column)) {
+            (std::cmp::Ordering::Less, _) => 'N',
+            (std::cmp::Ordering::Greater, _) => 'S',
+            (_, std::cmp::Ordering::Less) => 'W',
+            _ => 'E',
+        };
+        map[row][column] = match (towards(before), towards(after)) {
+            ('N', 'S') | ('S', 'N') => '|',
+            ('E', 'W') | ('W', 'E') => '-',
+            ('N', 'E') | ('E', 'N') => 'L',
+            ('N', 'W') | ('W', 'N') => 'J',
+            ('S', 'W') | ('W', 'S') => '7',
+            _ => 'F',
+        };
+    }
+    map[1][1] = 'S';
+    Ok(map.into_iter().map(String::from_iter).collect())
+}
+
+/// `lines` galaxies, leaving one row and column in 20 empty.
+fn image(rng: &mut StdRng, knobs: &Knobs) -> Result<Vec<String>> {
+    let full = |rng: &mut StdRng, size: usize| {
+        let mut full = rand::seq::index::sample(rng, size, size - size / 20).into_vec();
+        full.sort_unstable();
+        full
+    };
+    let rows = full(rng, knobs.height);
+    let columns = full(rng, knobs.width);
+    if rows.len() * columns.len() < knobs.lines {
+        bail!("day 11 cannot fit {} galaxies", knobs.lines);
+    }
+    let mut image = vec![vec!['.'; knobs.width]; knobs.height];
+    for cell in rand::seq::index::sample(rng, rows.len() * columns.len(), knobs.lines) {
+        image[rows[cell / columns.len()]][columns[cell % columns.len()]] = '#';
+    }
+    Ok(image.into_iter().map(String::from_iter).collect())
+}
+
+/// Rows of springs with some of them unknown, and the groups they had.
+fn springs(rng: &mut StdRng, knobs: &Knobs) -> Result<Vec<String>> {
+    if knobs.springs == 0 {
+        bail!("day 12 needs at least a spring per row");
+    }
+    Ok((0..knobs.lines)
+        .map(|_| {
+            let mut row = (0..knobs.springs)
+                .map(|_| rng.random_bool(0.5))
+                .collect::<Vec<bool>>();
+            let damaged = rng.random_range(0..knobs.springs);
+            row[damaged] = true;
+            let groups = row
+                .split(|damaged| !damaged)
+                .filter(|group| !group.is_empty())
+                .map(|group| group.len().to_string())
+                .collect::<Vec<String>>();
+            let springs = row
+                .iter()
+                .map(|damaged| match (rng.random_bool(0.5), damaged) {
+                    (true, _) => '?',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                })
+                .collect::<String>();
+            format!("{springs} {}", groups.join(","))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day1, day2, day8, DAYS};
+    use proptest::prelude::*;
+
+    #[test]
+    fn generate_real_sizes() -> Result<()> {
+        for solver in DAYS.iter().filter(|solver| has_generator(solver.day())) {
+            let day = solver.day();
+            let input = generate(day, 1, &Knobs::of(day))?;
+            assert_eq!(input, generate(day, 1, &Knobs::of(day))?);
+            // both parts are solved on small inputs below, part 2 is slow
+            // without optimisations
+            solver
+                .solve(&input, 1)
+                .map_err(|e| e.context(format!("day {day}")))?;
+        }
+        assert!(!has_generator(25));
+        assert!(generate(25, 1, &Knobs::of(25)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn generate_known_answers() -> Result<()> {
+        let knobs = Knobs {
+            lines: 20,
+            width: 5,
+            ..Knobs::of(8)
+        };
+        // one loop of AAA, 18 nodes and ZZZ
+        let input = generate(8, 7, &knobs)?;
+        assert_eq!(day8::day8_part1(input.clone())?, 19);
+        assert_eq!(day8::day8_part2(input)?, 19);
+        assert!(generate(6, 0, &Knobs::of(1)).is_err());
+        Ok(())
+    }
+
+    fn small() -> impl Strategy<Value = Knobs> {
+        (2..40usize, 5..30usize, 4..30usize, 1..10usize, 1..15usize).prop_map(
+            |(lines, width, height, ranges, springs)| Knobs {
+                lines,
+                width,
+                height,
+                ranges,
+                springs,
+            },
+        )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn generated_inputs_solve(seed in any::<u64>(), knobs in small()) {
+            for solver in DAYS.iter().filter(|solver| has_generator(solver.day())) {
+                let day = solver.day();
+                let lines = match day {
+                    6 => knobs.lines % 4 + 1,
+                    11 => knobs.lines.min(knobs.width * knobs.height / 2),
+                    _ => knobs.lines,
+                };
+                let knobs = Knobs { lines, ..knobs };
+                let input = generate(day, seed, &knobs).unwrap();
+                for part in [1, 2] {
+                    prop_assert!(solver.solve(&input, part).is_ok(), "day {} part {}:\n{}", day, part, input);
+                }
+            }
+        }
+
+        #[test]
+        fn refactors_agree_on_generated_inputs(seed in any::<u64>(), knobs in small()) {
+            let input = generate(1, seed, &knobs).unwrap();
+            prop_assert_eq!(
+                day1::day1_part2(input.as_bytes()).unwrap(),
+                day1::refactor::day1_part2(input.as_bytes()).unwrap()
+            );
+            let input = generate(2, seed, &knobs).unwrap();
+            prop_assert_eq!(
+                day2::day2_part2(input.as_bytes()).unwrap(),
+                day2::refactor::day2_part2(input.as_bytes()).unwrap()
+            );
+        }
+    }
+}
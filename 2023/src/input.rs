@@ -0,0 +1,24 @@
+//! Inputs of the tests.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+
+/// The bundled input of `day`, the one the answers of the tests are for.
+pub(crate) fn test_input(day: u8) -> Result<String> {
+    crate::CALENDAR
+        .inputs()
+        .input_dir(None)
+        .cache_dir(None)
+        .stdin(false)
+        .load(day)
+}
+
+/// The example `name` of `day` in `fixtures`.
+pub(crate) fn fixture(day: u8, name: &str) -> Result<String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(format!("day{day}"))
+        .join(format!("{name}.txt"));
+    fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))
+}
@@ -1,12 +1,215 @@
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
+//! Solutions to the Advent of Code 2023 puzzles.
+//!
+//! Every day lives in its own module with its parser, models and the
+//! `dayN_part1`/`dayN_part2` solvers. They are also registered in [`DAYS`] as
+//! [`Solver`]s so tools can run them without knowing each signature, and in
+//! [`CALENDAR`] for the `aoc` runner:
+//!
+//! ```
+//! let day6 = aoc_2023::get(6).unwrap();
+//! let answer = day6.solve("Time:      7  15   30\nDistance:  9  40  200", 1).unwrap();
+//! assert_eq!(answer.to_string(), "288");
+//! ```
+#![warn(missing_docs)]
+
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod gen;
+#[cfg(test)]
+mod input;
+
+pub use aoc_core::{error, grid, math, parsing, solution};
+
+use aoc_core::Calendar;
+use solution::Solver;
+
+/// The year of the puzzles of this crate.
+pub const YEAR: u16 = 2023;
+
+/// Every solved day, in calendar order.
+pub static DAYS: &[&dyn Solver] = &[
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+];
+
+/// The days of 2023, with their bundled inputs.
+pub static CALENDAR: Calendar = Calendar {
+    year: YEAR,
+    days: DAYS,
+    bundled_dir: concat!(env!("CARGO_MANIFEST_DIR"), "/src"),
+    module: module_path!(),
+};
+
+/// Looks up a registered day.
+pub fn get(day: u8) -> Option<&'static dyn Solver> {
+    CALENDAR.get(day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use anyhow::{Context, Result};
+    use aoc_core::{
+        error::Line,
+        normalise::{normalise, Mode, Normalised},
+        scaffold,
+        solution::{Answer, Solution, Solver},
+    };
+    use rstest::rstest;
+    use serde::Deserialize;
+
+    /// The answers stated by the puzzle for an example, `fixtures/dayN/x.toml`
+    /// next to the example `x.txt`.
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Expected {
+        part1: Option<String>,
+        part2: Option<String>,
+    }
+
+    #[rstest]
+    fn examples(#[files("fixtures/day*/*.txt")] path: PathBuf) -> Result<()> {
+        let day = fixture_day(&path)?;
+        let solver = crate::get(day).with_context(|| format!("day {day} is not registered"))?;
+        check_example(solver, day, &path)
+    }
+
+    /// The day of the fixture at `path`.
+    fn fixture_day(path: &Path) -> Result<u8> {
+        path.parent()
+            .and_then(|dir| dir.file_name()?.to_str()?.strip_prefix("day")?.parse().ok())
+            .context("fixtures live in a `dayN` directory")
+    }
+
+    /// Checks that every implementation of `solver` gives the answers of the
+    /// example of `day` at `path`, read whole, repaired or streamed.
+    fn check_example(solver: &dyn Solver, day: u8, path: &Path) -> Result<()> {
+        let input = fs::read_to_string(path)?;
+        let answers = path.with_extension("toml");
+        let expected: Expected = toml::from_str(
+            &fs::read_to_string(&answers)
+                .with_context(|| format!("reading {}", answers.display()))?,
+        )?;
+        let parts = [(1, expected.part1), (2, expected.part2)];
+        assert!(
+            parts.iter().any(|(_, answer)| answer.is_some()),
+            "{} has no answer",
+            answers.display()
+        );
+        // the same example saved with a BOM, CRLF, indentation, trailing
+        // whitespace and blank lines
+        let mangled = format!(
+            "\u{feff}{}\r\n\r\n",
+            input
+                .lines()
+                .map(|line| format!("  {line} \t\r\n"))
+                .collect::<String>()
+        );
+        let repaired = normalise(day, &mangled, Mode::Repair)?;
+        assert!(normalise(day, &mangled, Mode::Strict).is_err());
+        for (part, answer) in parts {
+            if let Some(answer) = answer {
+                for name in solver.implementation_names(part) {
+                    for input in [&input, &repaired] {
+                        let actual = solver.solve_with(name, input, part)?.to_string();
+                        assert_eq!(actual, answer, "{} part {part} {name}", path.display());
+                    }
+                }
+                // `aoc run` streams the input through the same normalisation
+                let mut reader = Normalised::new(day, mangled.as_bytes(), Mode::Repair);
+                let actual = solver.solve_reader(&mut reader, part)?.to_string();
+                assert_eq!(actual, answer, "{} part {part} streamed", path.display());
+                let mut reader = Normalised::new(day, mangled.as_bytes(), Mode::Strict);
+                assert!(solver.solve_reader(&mut reader, part).is_err());
+            }
+        }
+        Ok(())
+    }
+
+    /// The solver of `templates/day.rs.tmpl`, whatever its day.
+    struct Scaffolded;
+
+    impl Solution for Scaffolded {
+        const DAY: u8 = 25;
+        type Input = String;
+
+        fn parse(input: &str) -> Result<String> {
+            Ok(input.to_owned())
+        }
+
+        fn part1(input: &String) -> Result<Answer> {
+            Ok(Line::lines(Self::DAY, input).count().into())
+        }
+
+        fn part2(input: &String) -> Result<Answer> {
+            Ok(Line::lines(Self::DAY, input).count().into())
+        }
+    }
+
+    #[test]
+    fn scaffolded_example() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aoc-2023-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src"))?;
+        fs::copy(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/src/lib.rs"),
+            root.join("src").join("lib.rs"),
+        )?;
+        // a day that `aoc new` could still scaffold
+        let day = (1..=25)
+            .rev()
+            .find(|day| crate::get(*day).is_none())
+            .context("every day is registered")?;
+        let created = scaffold::scaffold(&root, day)?;
+        // the `examples` pattern picks the new fixture up
+        let examples = created
+            .iter()
+            .filter_map(|path| path.strip_prefix(&root).ok())
+            .filter(|path| path.starts_with("fixtures") && path.extension() == Some("txt".as_ref()))
+            .collect::<Vec<&Path>>();
+        let fixture = format!("fixtures/day{day}/example.txt");
+        assert_eq!(examples, [Path::new(&fixture)]);
+        let example = root.join(examples[0]);
+        assert_eq!(fixture_day(&example)?, day);
+        check_example(&Scaffolded, day, &example)?;
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn registry_days() {
+        let days: Vec<u8> = crate::DAYS.iter().map(|d| d.day()).collect();
+        // `aoc new` appends the days after 12
+        assert_eq!(days[..12], (1..=12).collect::<Vec<u8>>());
+        assert!(days.is_sorted_by(|a, b| a < b));
+        assert!(crate::get(12).is_some());
+        assert!(crate::get(0).is_none() && crate::get(26).is_none());
+        assert_eq!(
+            crate::get(2).unwrap().implementation_names(1),
+            ["default", "refactor"]
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! Expected answers stored in `answers.toml`.
+//!
+//! Every entry is an input, identified by the SHA-256 of its content, with
+//! the expected answer of each part:
+//!
+//! ```toml
+//! [[answer]]
+//! year = 2023
+//! day = 1
+//! input = "<sha256 of the input>"
+//! part1 = "54331"
+//! part2 = "54518"
+//! ```
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    input::InputProvider,
+    solution::{Answer, DEFAULT_IMPLEMENTATION},
+    Calendar,
+};
+
+/// `answers.toml` at the root of the workspace, shared by every year.
+pub fn default_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("answers.toml")
+}
+
+/// The expected answers of one input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Year of the puzzle.
+    pub year: u16,
+    /// Day of the puzzle.
+    pub day: u8,
+    /// SHA-256 of the input, see [`input_hash`].
+    pub input: String,
+    /// Expected answer of part 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part1: Option<String>,
+    /// Expected answer of part 2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part2: Option<String>,
+}
+
+/// Every known expected answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnswerStore {
+    #[serde(default, rename = "answer")]
+    entries: Vec<Entry>,
+}
+
+impl AnswerStore {
+    /// Parses the TOML content of an answers file.
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Loads an answers file, a missing file is an empty store.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                Self::parse(&content).with_context(|| format!("parsing {}", path.display()))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+        }
+    }
+
+    /// Writes the store back as TOML.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, toml::to_string(self)?)
+            .with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Every entry, in file order.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// The expected answer of `part` for the input hashed as `input`.
+    pub fn expected(&self, year: u16, day: u8, input: &str, part: u8) -> Option<&str> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|e| e.year == year && e.day == day && e.input == input)?;
+        match part {
+            1 => entry.part1.as_deref(),
+            2 => entry.part2.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Records the answer of `part`, replacing a previous one.
+    pub fn record(&mut self, year: u16, day: u8, input: &str, part: u8, answer: &Answer) {
+        let index = match self
+            .entries
+            .iter()
+            .position(|e| e.year == year && e.day == day && e.input == input)
+        {
+            Some(index) => index,
+            None => {
+                self.entries.push(Entry {
+                    year,
+                    day,
+                    input: input.to_owned(),
+                    part1: None,
+                    part2: None,
+                });
+                self.entries.len() - 1
+            }
+        };
+        let entry = &mut self.entries[index];
+        let slot = if part == 1 {
+            &mut entry.part1
+        } else {
+            &mut entry.part2
+        };
+        *slot = Some(answer.to_string());
+    }
+}
+
+/// SHA-256 of an input, as lowercase hex.
+pub fn input_hash(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// The outcome of running a solver against a stored answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The answer is the expected one.
+    Ok,
+    /// The answer differs from the expected one.
+    Mismatch,
+    /// There is no expected answer for this input.
+    Unknown,
+    /// The solver failed.
+    Failed(String),
+}
+
+/// One solver run of [`verify`].
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// Year of the puzzle.
+    pub year: u16,
+    /// Day of the puzzle.
+    pub day: u8,
+    /// Part of the puzzle.
+    pub part: u8,
+    /// Name of the implementation that solved it.
+    pub implementation: &'static str,
+    /// Where the input came from.
+    pub source: String,
+    /// Hash of the input, empty when it could not be read.
+    pub input: String,
+    /// The stored answer, if any.
+    pub expected: Option<String>,
+    /// What the solver answered.
+    pub actual: Option<String>,
+    /// How both compare.
+    pub status: Status,
+}
+
+/// Runs every implementation of every day of the calendar on every input of
+/// the provider and compares the answers with the store. An input that cannot
+/// be read, or that the normalisation mode rejects, fails both parts.
+pub fn verify(store: &AnswerStore, calendar: &Calendar, provider: &InputProvider) -> Vec<Check> {
+    let year = calendar.year;
+    let mut checks = vec![];
+    for solver in calendar.days {
+        let day = solver.day();
+        let mut seen = vec![];
+        for source in provider.inputs(day) {
+            let input = match provider.read(day, &source) {
+                Ok(input) => input,
+                Err(e) => {
+                    checks.extend([1, 2].map(|part| Check {
+                        year,
+                        day,
+                        part,
+                        implementation: DEFAULT_IMPLEMENTATION,
+                        source: source.to_string(),
+                        input: String::new(),
+                        expected: None,
+                        actual: None,
+                        status: Status::Failed(format!("{e:#}")),
+                    }));
+                    continue;
+                }
+            };
+            let hash = input_hash(&input);
+            if seen.contains(&hash) {
+                continue;
+            }
+            for (part, implementation) in [1, 2].into_iter().flat_map(|part| {
+                solver
+                    .implementation_names(part)
+                    .into_iter()
+                    .map(move |name| (part, name))
+            }) {
+                let expected = store.expected(year, day, &hash, part).map(str::to_owned);
+                let (actual, status) = match solver.solve_with(implementation, &input, part) {
+                    Ok(answer) => {
+                        let answer = answer.to_string();
+                        let status = match &expected {
+                            Some(expected) if *expected == answer => Status::Ok,
+                            Some(_) => Status::Mismatch,
+                            None => Status::Unknown,
+                        };
+                        (Some(answer), status)
+                    }
+                    Err(e) => (None, Status::Failed(e.to_string())),
+                };
+                checks.push(Check {
+                    year,
+                    day,
+                    part,
+                    implementation,
+                    source: source.to_string(),
+                    input: hash.clone(),
+                    expected,
+                    actual,
+                    status,
+                });
+            }
+            seen.push(hash);
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calendar::TEST_CALENDAR, normalise::Mode};
+
+    #[test]
+    fn store_lookup() -> Result<()> {
+        let store = AnswerStore::parse(
+            r#"
+[[answer]]
+year = 2023
+day = 1
+input = "abc"
+part1 = "142"
+
+[[answer]]
+year = 2023
+day = 1
+input = "def"
+part1 = "54331"
+part2 = "54518"
+"#,
+        )?;
+        assert_eq!(store.expected(2023, 1, "abc", 1), Some("142"));
+        assert_eq!(store.expected(2023, 1, "abc", 2), None);
+        assert_eq!(store.expected(2023, 1, "def", 2), Some("54518"));
+        assert_eq!(store.expected(2022, 1, "def", 2), None);
+        Ok(())
+    }
+
+    #[test]
+    fn store_record() -> Result<()> {
+        let mut store = AnswerStore::default();
+        store.record(2023, 9, "abc", 2, &Answer::Signed(-2));
+        store.record(2023, 9, "abc", 1, &Answer::Signed(114));
+        assert_eq!(store.entries().len(), 1);
+        let reparsed = AnswerStore::parse(&toml::to_string(&store)?)?;
+        assert_eq!(reparsed.expected(2023, 9, "abc", 1), Some("114"));
+        assert_eq!(reparsed.expected(2023, 9, "abc", 2), Some("-2"));
+        Ok(())
+    }
+
+    #[test]
+    fn verify_mismatch() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc-answers-{}", std::process::id()));
+        fs::create_dir_all(dir.join("day1"))?;
+        let input = "1\n2\n3";
+        fs::write(dir.join("day1").join("sample.txt"), input)?;
+        let provider = InputProvider::new(2023)
+            .input_dir(None)
+            .cache_dir(None)
+            .bundled_dir(Some(dir.clone()))
+            .stdin(false);
+        let mut store = AnswerStore::default();
+        // only the `wrong` implementation, which forgets the 3, answers this
+        store.record(2023, 1, &input_hash(input), 1, &Answer::Unsigned(3));
+
+        let checks = verify(&store, &TEST_CALENDAR, &provider);
+        fs::remove_dir_all(dir)?;
+        let statuses = checks
+            .iter()
+            .map(|c| (c.part, c.implementation, &c.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                (1, "default", &Status::Mismatch),
+                (1, "reversed", &Status::Mismatch),
+                (1, "wrong", &Status::Ok),
+                (2, "default", &Status::Unknown),
+            ]
+        );
+        assert_eq!(checks[0].actual.as_deref(), Some("6"));
+        Ok(())
+    }
+
+    #[test]
+    fn verify_unreadable() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc-unreadable-{}", std::process::id()));
+        fs::create_dir_all(dir.join("day1"))?;
+        fs::write(dir.join("day1").join("sample.txt"), "  1\n  2")?;
+        let provider = InputProvider::new(2023)
+            .input_dir(None)
+            .cache_dir(None)
+            .bundled_dir(Some(dir.clone()))
+            .stdin(false)
+            .normalise(Mode::Strict);
+
+        let checks = verify(&AnswerStore::default(), &TEST_CALENDAR, &provider);
+        fs::remove_dir_all(dir)?;
+        assert_eq!(checks.len(), 2);
+        for check in &checks {
+            let Status::Failed(e) = &check.status else {
+                panic!("expected {check:?} to fail");
+            };
+            assert!(e.contains("indented"), "{e}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn hash() {
+        assert_eq!(
+            input_hash(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
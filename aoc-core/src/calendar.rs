@@ -0,0 +1,93 @@
+//! The registered days of one year.
+
+use std::path::Path;
+
+use crate::{input::InputProvider, solution::Solver};
+
+/// Every solved day of a year, registered by its crate.
+#[derive(Clone, Copy)]
+pub struct Calendar {
+    /// The year of the puzzles.
+    pub year: u16,
+    /// The solved days, in calendar order.
+    pub days: &'static [&'static dyn Solver],
+    /// Directory of the inputs committed with the crate, laid out like
+    /// `src`: the input of day 5 is `day5/input.txt`.
+    pub bundled_dir: &'static str,
+    /// Name of the crate, the target of its traces.
+    pub module: &'static str,
+}
+
+impl Calendar {
+    /// Looks up a registered day.
+    pub fn get(&self, day: u8) -> Option<&'static dyn Solver> {
+        self.days.iter().copied().find(|solver| solver.day() == day)
+    }
+
+    /// An input provider for this year, with the bundled inputs.
+    pub fn inputs(&self) -> InputProvider {
+        InputProvider::new(self.year).bundled_dir(Some(Path::new(self.bundled_dir).to_owned()))
+    }
+}
+
+/// A calendar of two days reading one number per line, part 1 sums them and
+/// part 2 takes the largest.
+#[cfg(test)]
+pub(crate) static TEST_CALENDAR: Calendar = Calendar {
+    year: 2023,
+    days: &[&tests::Numbers::<1>, &tests::Numbers::<2>],
+    bundled_dir: "",
+    module: module_path!(),
+};
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use anyhow::Result;
+
+    use crate::solution::{Answer, Implementation, Solution};
+
+    pub(crate) struct Numbers<const DAY: u8>;
+
+    impl<const DAY: u8> Solution for Numbers<DAY> {
+        const DAY: u8 = DAY;
+        type Input = Vec<u64>;
+        // part 1 again, and a part 1 that forgets the last number
+        const IMPLEMENTATIONS: &'static [Implementation] = &[
+            Implementation {
+                name: "reversed",
+                part: 1,
+                solve: |input| {
+                    let numbers = Self::parse(input)?;
+                    Ok(numbers.iter().rev().sum::<u64>().into())
+                },
+            },
+            Implementation {
+                name: "wrong",
+                part: 1,
+                solve: |input| {
+                    let numbers = Self::parse(input)?;
+                    Ok(numbers.iter().rev().skip(1).sum::<u64>().into())
+                },
+            },
+        ];
+
+        fn parse(input: &str) -> Result<Vec<u64>> {
+            Ok(input.lines().map(str::parse).collect::<Result<_, _>>()?)
+        }
+
+        fn part1(input: &Vec<u64>) -> Result<Answer> {
+            Ok(input.iter().sum::<u64>().into())
+        }
+
+        fn part2(input: &Vec<u64>) -> Result<Answer> {
+            Ok(input.iter().copied().max().unwrap_or_default().into())
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        let calendar = super::TEST_CALENDAR;
+        assert_eq!(calendar.get(2).map(|day| day.day()), Some(2));
+        assert!(calendar.get(3).is_none());
+    }
+}
@@ -0,0 +1,269 @@
+//! Errors reported by the puzzle parsers.
+
+use std::{
+    any::type_name,
+    error::Error,
+    fmt,
+    io::{self, BufRead},
+    str::FromStr,
+};
+
+use crate::math::CheckedAdd;
+
+/// A malformed or truncated puzzle input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Day of the puzzle being parsed.
+    pub day: u8,
+    /// Line of the input, starting at 1.
+    pub line: usize,
+    /// Column of the line in characters, starting at 1.
+    pub column: usize,
+    /// The offending text, empty when the input ended too early.
+    pub text: String,
+    /// What was expected.
+    pub message: String,
+}
+
+impl ParseError {
+    /// An error at `line` (starting at 1) and `column` (starting at 1).
+    pub fn new(
+        day: u8,
+        line: usize,
+        column: usize,
+        text: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        ParseError {
+            day,
+            line,
+            column,
+            text: text.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The input ended before the `line` it needed.
+    pub fn truncated(day: u8, line: usize, message: impl Into<String>) -> Self {
+        ParseError::new(day, line, 1, "", message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {} line {} column {}: {}",
+            self.day, self.line, self.column, self.message
+        )?;
+        if self.text.is_empty() {
+            write!(f, " (unexpected end of input)")
+        } else {
+            write!(f, ", found `{}`", self.text)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// An input that could not be read, or is malformed.
+#[derive(Debug)]
+pub enum InputError {
+    /// Reading the input failed, it may not be UTF-8.
+    Read(io::Error),
+    /// The input is malformed.
+    Parse(ParseError),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Read(e) => write!(f, "reading the input: {e}"),
+            InputError::Parse(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::Read(e) => Some(e),
+            InputError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for InputError {
+    fn from(error: io::Error) -> Self {
+        // the normalised readers reject an input through an io error
+        match error.get_ref().and_then(|e| e.downcast_ref::<ParseError>()) {
+            Some(e) => InputError::Parse(e.clone()),
+            None => InputError::Read(error),
+        }
+    }
+}
+
+impl From<ParseError> for InputError {
+    fn from(error: ParseError) -> Self {
+        InputError::Parse(error)
+    }
+}
+
+/// A line of a puzzle input, to point parse errors at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    /// Day of the puzzle.
+    pub day: u8,
+    /// Line number, starting at 1.
+    pub number: usize,
+    /// The line without its ending.
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// The numbered lines of `input`.
+    pub fn lines(day: u8, input: &'a str) -> impl Iterator<Item = Line<'a>> {
+        input.lines().enumerate().map(move |(i, text)| Line {
+            day,
+            number: i + 1,
+            text,
+        })
+    }
+
+    /// Calls `f` on the numbered lines of `reader`, only one of them is in
+    /// memory at a time. Stops at the first error.
+    pub fn read_each(
+        day: u8,
+        mut reader: impl BufRead,
+        mut f: impl FnMut(Line<'_>) -> Result<(), ParseError>,
+    ) -> Result<(), InputError> {
+        let mut text = String::new();
+        let mut number = 0;
+        loop {
+            text.clear();
+            if reader.read_line(&mut text)? == 0 {
+                return Ok(());
+            }
+            number += 1;
+            // the same endings as `str::lines`
+            let text = match text.strip_suffix('\n') {
+                Some(text) => text.strip_suffix('\r').unwrap_or(text),
+                None => &text,
+            };
+            f(Line { day, number, text })?;
+        }
+    }
+
+    /// Sums `f` over the lines of `reader`, see [`Line::read_each`]. Fails on
+    /// the line where the sum overflows `T`.
+    pub fn sum_each<T: Default + Copy + CheckedAdd>(
+        day: u8,
+        reader: impl BufRead,
+        mut f: impl FnMut(Line<'_>) -> Result<T, ParseError>,
+    ) -> Result<T, InputError> {
+        let mut sum = T::default();
+        Line::read_each(day, reader, |line| {
+            sum = sum.checked_add(f(line)?).ok_or_else(|| {
+                let message = format!("expected the sum to fit in {}", type_name::<T>());
+                line.error(line.text, message)
+            })?;
+            Ok(())
+        })?;
+        Ok(sum)
+    }
+
+    /// An error about `at`, which should be a slice of this line to get its
+    /// column right.
+    pub fn error(&self, at: &str, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.day, self.number, self.column(at), at, message)
+    }
+
+    /// Column of `at` inside the line, 1 when it is not a slice of the line.
+    pub fn column(&self, at: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (at.as_ptr() as usize).wrapping_sub(start);
+        if offset <= self.text.len() && self.text.is_char_boundary(offset) {
+            self.text[..offset].chars().count() + 1
+        } else {
+            1
+        }
+    }
+
+    /// Parses `token`, a slice of this line.
+    pub fn parse<T: FromStr>(&self, token: &str, what: &str) -> Result<T, ParseError> {
+        token
+            .parse()
+            .map_err(|_| self.error(token, format!("expected {what}")))
+    }
+
+    /// Splits the line, or a slice of it, around the first `delimiter`.
+    pub fn split_once(
+        &self,
+        text: &'a str,
+        delimiter: &str,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        text.split_once(delimiter)
+            .ok_or_else(|| self.error(text, format!("expected `{delimiter}`")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_and_column() {
+        let input = "Game 1: 3 blue\nGame 2: x red";
+        let line = Line::lines(2, input).nth(1).unwrap();
+        let (amount, _) = line.split_once(&line.text[8..], " ").unwrap();
+        let error = line.parse::<usize>(amount, "a number").unwrap_err();
+        assert_eq!(error, ParseError::new(2, 2, 9, "x", "expected a number"));
+        assert_eq!(
+            error.to_string(),
+            "day 2 line 2 column 9: expected a number, found `x`"
+        );
+    }
+
+    #[test]
+    fn read_lines() {
+        let mut lines = vec![];
+        Line::read_each(0, "a\r\nb\n\nc\r".as_bytes(), |line| {
+            lines.push((line.number, line.text.to_owned()));
+            Ok(())
+        })
+        .unwrap();
+        let expected = Line::lines(0, "a\r\nb\n\nc\r")
+            .map(|line| (line.number, line.text.to_owned()))
+            .collect::<Vec<(usize, String)>>();
+        assert_eq!(lines, expected);
+
+        let error = Line::read_each(0, "a\nb".as_bytes(), |line| match line.text {
+            "b" => Err(line.error(line.text, "expected a")),
+            _ => Ok(()),
+        });
+        assert!(matches!(error, Err(InputError::Parse(e)) if e.line == 2));
+        let error = Line::read_each(0, &b"a\n\xff\n"[..], |_| Ok(()));
+        assert!(matches!(error, Err(InputError::Read(_))));
+    }
+
+    #[test]
+    fn sum_overflow() {
+        let sum = Line::sum_each(1, "4294967294\n1\n".as_bytes(), |line| {
+            line.parse::<u32>(line.text, "a number")
+        });
+        assert_eq!(sum.unwrap(), u32::MAX);
+        let sum = Line::sum_each(1, "4294967295\n0\n1\n".as_bytes(), |line| {
+            line.parse::<u32>(line.text, "a number")
+        });
+        let expected = ParseError::new(1, 3, 1, "1", "expected the sum to fit in u32");
+        assert!(matches!(sum, Err(InputError::Parse(e)) if e == expected));
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(
+            ParseError::truncated(5, 1, "expected the seeds").to_string(),
+            "day 5 line 1 column 1: expected the seeds (unexpected end of input)"
+        );
+    }
+}
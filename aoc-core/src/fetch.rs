@@ -0,0 +1,301 @@
+//! Downloads puzzle inputs from the Advent of Code website.
+//!
+//! Requests need the `session` cookie of a logged in browser, read from
+//! `AOC_SESSION`. Downloaded inputs are cached on disk, requests are spaced by
+//! a minimum interval and carry a User-Agent naming this crate, as the site
+//! asks of automated tools.
+
+use std::{
+    env, fmt, fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::input::{day_path, default_cache_dir};
+
+/// Environment variable holding the session cookie.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+/// Environment variable overriding [`DEFAULT_BASE_URL`].
+pub const BASE_URL_VAR: &str = "AOC_BASE_URL";
+/// Environment variable overriding [`DEFAULT_USER_AGENT`].
+pub const USER_AGENT_VAR: &str = "AOC_USER_AGENT";
+
+/// The Advent of Code website.
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+/// User-Agent sent when `AOC_USER_AGENT` is not set.
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (github.com/fcabjolsky/advent-of-code)"
+);
+/// Default minimum time between two requests.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// HTTP client for the puzzles of one year of the Advent of Code website.
+#[derive(Clone)]
+pub struct Client {
+    year: u16,
+    base_url: String,
+    session: Option<String>,
+    user_agent: String,
+    cache_dir: Option<PathBuf>,
+    interval: Duration,
+    last_request: Arc<Mutex<Option<Instant>>>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("year", &self.year)
+            .field("base_url", &self.base_url)
+            .field("session", &self.session.as_ref().map(|_| "<hidden>"))
+            .field("user_agent", &self.user_agent)
+            .field("cache_dir", &self.cache_dir)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl Client {
+    /// A client for the puzzles of `year` configured from `AOC_SESSION`,
+    /// `AOC_BASE_URL` and `AOC_USER_AGENT`, caching in the per-user cache
+    /// directory of the year.
+    pub fn new(year: u16) -> Self {
+        Client {
+            year,
+            base_url: env::var(BASE_URL_VAR).unwrap_or_else(|_| DEFAULT_BASE_URL.to_owned()),
+            session: env::var(SESSION_VAR).ok().filter(|s| !s.trim().is_empty()),
+            user_agent: env::var(USER_AGENT_VAR).unwrap_or_else(|_| DEFAULT_USER_AGENT.to_owned()),
+            cache_dir: default_cache_dir(year),
+            interval: DEFAULT_INTERVAL,
+            last_request: Arc::default(),
+        }
+    }
+
+    /// Overrides the website, for a local server in tests.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_owned();
+        self
+    }
+
+    /// Overrides the session cookie.
+    pub fn session(mut self, session: Option<String>) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Overrides the User-Agent.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Overrides the directory downloaded inputs are cached in, `None` to
+    /// always download.
+    pub fn cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.cache_dir = dir;
+        self
+    }
+
+    /// Overrides the minimum time between two requests.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The year of the puzzles.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Whether a session cookie is configured.
+    pub fn has_session(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// The input of `day`, from the cache or downloaded into it.
+    pub fn input(&self, day: u8) -> Result<String> {
+        let cached = self.cache_dir.as_ref().map(|dir| day_path(dir, day));
+        if let Some(path) = cached.as_ref().filter(|path| path.is_file()) {
+            return fs::read_to_string(path)
+                .with_context(|| format!("reading cached input {}", path.display()));
+        }
+        let input = self.download(day)?;
+        if let Some(path) = cached {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("creating cache {}", dir.display()))?;
+            }
+            fs::write(&path, &input)
+                .with_context(|| format!("caching input {}", path.display()))?;
+        }
+        Ok(input)
+    }
+
+    /// Downloads the input of `day`, ignoring the cache.
+    pub fn download(&self, day: u8) -> Result<String> {
+        self.get(&format!("/{}/day/{day}/input", self.year))
+            .with_context(|| format!("downloading the input of day {day}"))
+    }
+
+    /// `GET`s a page of the website.
+    pub fn get(&self, path: &str) -> Result<String> {
+        let request = self.request("GET", path)?;
+        Self::body(request.call())
+    }
+
+    /// `POST`s a form to the website.
+    pub fn post(&self, path: &str, form: &[(&str, &str)]) -> Result<String> {
+        let request = self.request("POST", path)?;
+        Self::body(request.send_form(form))
+    }
+
+    fn request(&self, method: &str, path: &str) -> Result<ureq::Request> {
+        let Some(session) = &self.session else {
+            bail!("set {SESSION_VAR} to the session cookie of the Advent of Code website");
+        };
+        self.throttle();
+        Ok(ureq::request(method, &format!("{}{path}", self.base_url))
+            .set("Cookie", &format!("session={session}"))
+            .set("User-Agent", &self.user_agent))
+    }
+
+    fn body(response: Result<ureq::Response, ureq::Error>) -> Result<String> {
+        match response {
+            Ok(response) => Ok(response.into_string()?),
+            Err(ureq::Error::Status(404, _)) => bail!("the puzzle is not unlocked yet"),
+            Err(ureq::Error::Status(code @ (400 | 500), _)) => {
+                bail!("request refused ({code}), the session cookie may have expired")
+            }
+            Err(ureq::Error::Status(code, _)) => bail!("request failed ({code})"),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Waits until the interval since the previous request has passed.
+    fn throttle(&self) {
+        let mut last = self.last_request.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(wait) = last.and_then(|last| self.interval.checked_sub(last.elapsed())) {
+            thread::sleep(wait);
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+/// A local HTTP server answering `responses` in order, one per connection.
+/// Joining the handle returns the raw requests it received.
+#[cfg(test)]
+pub(crate) fn mock_server(
+    responses: Vec<(u16, &'static str)>,
+) -> (String, thread::JoinHandle<Vec<String>>) {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let mut requests = vec![];
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                request.push_str(&line);
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+            let mut content = vec![0; length];
+            reader.read_exact(&mut content).unwrap();
+            request.push_str(&String::from_utf8_lossy(&content));
+            requests.push(request);
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {status} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(url: &str, name: &str) -> (Client, PathBuf) {
+        let cache = env::temp_dir().join(format!("aoc-fetch-{name}-{}", std::process::id()));
+        let client = Client::new(2023)
+            .base_url(url)
+            .session(Some("abc123".to_owned()))
+            .user_agent("aoc-test")
+            .cache_dir(Some(cache.clone()))
+            .interval(Duration::ZERO);
+        (client, cache)
+    }
+
+    #[test]
+    fn fetch_and_cache() -> Result<()> {
+        let (url, server) = mock_server(vec![(200, "1abc2\n")]);
+        let (client, cache) = client(&url, "cache");
+
+        assert_eq!(client.input(1)?, "1abc2\n");
+        // the server is gone, so this one must come from the cache
+        assert_eq!(client.input(1)?, "1abc2\n");
+        assert_eq!(fs::read_to_string(day_path(&cache, 1))?, "1abc2\n");
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /2023/day/1/input HTTP/1.1"));
+        assert!(requests[0].contains("Cookie: session=abc123"));
+        assert!(requests[0].contains("User-Agent: aoc-test"));
+        fs::remove_dir_all(cache)?;
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_errors() -> Result<()> {
+        let (url, server) = mock_server(vec![(404, "Not found"), (400, "Bad request")]);
+        let (client, _) = client(&url, "errors");
+        let client = client.cache_dir(None);
+
+        let error = client.input(25).unwrap_err();
+        assert!(format!("{error:#}").contains("not unlocked"));
+        let error = client.input(25).unwrap_err();
+        assert!(format!("{error:#}").contains("session cookie"));
+        assert!(client.clone().session(None).input(25).is_err());
+        assert_eq!(server.join().unwrap().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn throttle() -> Result<()> {
+        let (url, server) = mock_server(vec![(200, "a"), (200, "b")]);
+        let (client, _) = client(&url, "throttle");
+        let client = client.interval(Duration::from_millis(200));
+
+        let start = Instant::now();
+        client.download(1)?;
+        client.download(2)?;
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        server.join().unwrap();
+        Ok(())
+    }
+}
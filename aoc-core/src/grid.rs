@@ -0,0 +1,316 @@
+//! A rectangular 2D grid, for the days whose input is a map.
+//!
+//! Positions are `(row, column)` pairs starting at the top left corner.
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    error::{Line, ParseError},
+    parsing::{cells, parse_line},
+};
+
+/// A `(row, column)` position.
+pub type Pos = (usize, usize);
+
+/// Offsets of the 4 orthogonal neighbours: up, left, right, down.
+pub const NEIGHBOURS4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Offsets of the 8 neighbours, row by row.
+pub const NEIGHBOURS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid of cells stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid of `width` columns from its cells row by row, `None` when they
+    /// do not fill whole rows.
+    pub fn new(width: usize, cells: Vec<T>) -> Option<Self> {
+        if width == 0 {
+            return cells.is_empty().then_some(Grid {
+                width,
+                height: 0,
+                cells,
+            });
+        }
+        cells.len().is_multiple_of(width).then(|| Grid {
+            width,
+            height: cells.len() / width,
+            cells,
+        })
+    }
+
+    /// Parses each character of `input` with `cell`, every line is a row.
+    /// Rows must all have the same length, a character `cell` rejects is an
+    /// error expecting `what`.
+    pub fn parse_with(
+        day: u8,
+        input: &str,
+        what: &str,
+        cell: impl Fn(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut grid = vec![];
+        for line in Line::lines(day, input) {
+            let row = parse_line(&line, what, cells(&cell))?;
+            let count = row.len();
+            grid.extend(row);
+            match width {
+                None => width = Some(count),
+                Some(width) if width != count => {
+                    return Err(line.error(line.text, format!("expected a row of {width} columns")))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(Grid::new(width.unwrap_or(0), grid).expect("rows have the same width"))
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether `pos` is inside the grid.
+    pub fn contains(&self, (row, column): Pos) -> bool {
+        row < self.height && column < self.width
+    }
+
+    /// The cell at `pos`, `None` outside the grid.
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.0 * self.width + pos.1])
+    }
+
+    /// The mutable cell at `pos`, `None` outside the grid.
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// `pos` moved by `(rows, columns)`, `None` when it leaves the grid.
+    pub fn offset(&self, (row, column): Pos, (rows, columns): (isize, isize)) -> Option<Pos> {
+        let pos = (
+            row.checked_add_signed(rows)?,
+            column.checked_add_signed(columns)?,
+        );
+        self.contains(pos).then_some(pos)
+    }
+
+    /// The cell `(rows, columns)` away from `pos`, `None` outside the grid.
+    pub fn get_offset(&self, pos: Pos, delta: (isize, isize)) -> Option<&T> {
+        self.get(self.offset(pos, delta)?)
+    }
+
+    /// The orthogonal neighbours of `pos` inside the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS4
+            .into_iter()
+            .filter_map(move |delta| self.offset(pos, delta))
+    }
+
+    /// The orthogonal and diagonal neighbours of `pos` inside the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS8
+            .into_iter()
+            .filter_map(move |delta| self.offset(pos, delta))
+    }
+
+    /// The rows, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on 0, an empty grid has no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The cells of `column`, top to bottom.
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        self.cells
+            .iter()
+            .skip(column)
+            .step_by(self.width.max(1))
+            .take(if column < self.width { self.height } else { 0 })
+    }
+
+    /// The columns, left to right.
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|column| self.column(column))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| ((i / self.width, i % self.width), cell))
+    }
+
+    /// Position of the first cell, row by row, matching `predicate`.
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// A grid of `width` by `height` cells set to `value`.
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Rows become columns.
+    pub fn transpose(&self) -> Self {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flatten().cloned().collect(),
+        }
+    }
+
+    /// Rotated a quarter turn clockwise.
+    pub fn rotate(&self) -> Self {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self
+                .columns()
+                .flat_map(|column| column.collect::<Vec<&T>>().into_iter().rev())
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    /// Every line of `input` is a row of characters.
+    pub fn parse(day: u8, input: &str) -> Result<Self, ParseError> {
+        Grid::parse_with(day, input, "a character", Some)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_get() {
+        let grid = Grid::parse(0, "ab\ncd\nef").unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get_offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.get_offset((0, 0), (1, 1)), Some(&'d'));
+        assert_eq!(grid.position(|c| *c == 'e'), Some((2, 0)));
+        assert_eq!(grid.to_string(), "ab\ncd\nef");
+
+        let error = Grid::parse(0, "ab\ncde").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(0, 2, 1, "cde", "expected a row of 2 columns")
+        );
+        let error = Grid::parse_with(0, ".#\n.x", "`.` or `#`", |c| {
+            (c == '.' || c == '#').then_some(c)
+        })
+        .unwrap_err();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 2, "x"));
+        assert_eq!(Grid::parse(0, "").unwrap().height(), 0);
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::filled(3, 3, 0);
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<Pos>>(),
+            [(0, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbours8((2, 2)).collect::<Vec<Pos>>(),
+            [(1, 1), (1, 2), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn rows_columns_and_turns() {
+        let grid = Grid::new(3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(grid.rows().collect::<Vec<&[i32]>>(), [[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(grid.column(1).collect::<Vec<&i32>>(), [&2, &5]);
+        assert_eq!(grid.column(3).count(), 0);
+        assert_eq!(
+            grid.transpose(),
+            Grid::new(2, vec![1, 4, 2, 5, 3, 6]).unwrap()
+        );
+        assert_eq!(grid.rotate(), Grid::new(2, vec![4, 1, 5, 2, 6, 3]).unwrap());
+        assert_eq!(grid.rotate().rotate().rotate().rotate(), grid);
+        assert_eq!(grid.map(|n| n * 2)[(1, 2)], 12);
+        assert!(Grid::new(4, vec![1, 2, 3]).is_none());
+    }
+}
@@ -0,0 +1,355 @@
+//! Puzzle input loading.
+//!
+//! Inputs are looked up, in order, at an explicit path, in the directory named
+//! by `AOC_INPUT_DIR`, in the per-user cache directory of the year, in the
+//! inputs bundled with the year crate, then downloaded into the cache when a
+//! [`Client`] is set and finally on stdin. Directories use the same layout as
+//! `src`: the input of day 5 is `day5/input.txt`. The provider normalises what
+//! it reads, see [`normalise`](crate::normalise).
+
+use std::{
+    env, fmt, fs,
+    io::{self, BufRead, BufReader, IsTerminal, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    fetch::Client,
+    normalise::{normalise, Mode, Normalised},
+};
+
+/// Environment variable naming a directory of private inputs.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Where an input was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A file given explicitly.
+    Path(PathBuf),
+    /// A file under `AOC_INPUT_DIR`.
+    InputDir(PathBuf),
+    /// A file in the per-user cache.
+    Cache(PathBuf),
+    /// A file committed next to the day module.
+    Bundled(PathBuf),
+    /// The standard input.
+    Stdin,
+}
+
+impl Source {
+    /// The file backing this source, `None` for stdin.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Source::Path(path)
+            | Source::InputDir(path)
+            | Source::Cache(path)
+            | Source::Bundled(path) => Some(path),
+            Source::Stdin => None,
+        }
+    }
+
+    /// Reads the whole input.
+    pub fn read(&self) -> Result<String> {
+        match self.path() {
+            Some(path) => fs::read_to_string(path)
+                .with_context(|| format!("reading input {}", path.display())),
+            None => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .context("reading input from stdin")?;
+                Ok(input)
+            }
+        }
+    }
+
+    /// Opens the input for reading line by line, without loading it whole.
+    pub fn open(&self) -> Result<Box<dyn BufRead>> {
+        match self.path() {
+            Some(path) => {
+                let file = fs::File::open(path)
+                    .with_context(|| format!("opening input {}", path.display()))?;
+                Ok(Box::new(BufReader::new(file)))
+            }
+            None => Ok(Box::new(io::stdin().lock())),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path() {
+            Some(path) => write!(f, "{}", path.display()),
+            None => write!(f, "stdin"),
+        }
+    }
+}
+
+/// Resolves the input of a day from the configured sources.
+#[derive(Debug, Clone)]
+pub struct InputProvider {
+    path: Option<PathBuf>,
+    input_dir: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    bundled_dir: Option<PathBuf>,
+    fetch: Option<Client>,
+    stdin: bool,
+    mode: Mode,
+}
+
+impl InputProvider {
+    /// A provider of the inputs of `year` using `AOC_INPUT_DIR`, the user
+    /// cache and stdin, see [`Calendar::inputs`](crate::Calendar::inputs) for
+    /// the bundled inputs.
+    pub fn new(year: u16) -> Self {
+        InputProvider {
+            path: None,
+            input_dir: env::var_os(INPUT_DIR_VAR).map(PathBuf::from),
+            cache_dir: default_cache_dir(year),
+            bundled_dir: None,
+            fetch: None,
+            stdin: true,
+            mode: Mode::Repair,
+        }
+    }
+
+    /// Uses this file before any other source.
+    pub fn path(mut self, path: Option<PathBuf>) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Overrides the directory read from `AOC_INPUT_DIR`.
+    pub fn input_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.input_dir = dir;
+        self
+    }
+
+    /// Overrides the per-user cache directory.
+    pub fn cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.cache_dir = dir;
+        self
+    }
+
+    /// Overrides the directory of the bundled inputs.
+    pub fn bundled_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.bundled_dir = dir;
+        self
+    }
+
+    /// Downloads missing inputs into the cache directory with this client.
+    pub fn fetch(mut self, client: Option<Client>) -> Self {
+        self.fetch = client;
+        self
+    }
+
+    /// Whether to fall back to stdin when it is not a terminal.
+    pub fn stdin(mut self, enabled: bool) -> Self {
+        self.stdin = enabled;
+        self
+    }
+
+    /// Whether to repair or reject the formatting of the inputs, they are
+    /// repaired by default.
+    pub fn normalise(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The configured cache directory.
+    pub fn cache(&self) -> Option<&Path> {
+        self.cache_dir.as_deref()
+    }
+
+    /// The configured directories, in lookup order.
+    fn dirs(&self) -> impl Iterator<Item = (&PathBuf, fn(PathBuf) -> Source)> {
+        [
+            (&self.input_dir, Source::InputDir as fn(PathBuf) -> Source),
+            (&self.cache_dir, Source::Cache),
+            (&self.bundled_dir, Source::Bundled),
+        ]
+        .into_iter()
+        .filter_map(|(dir, source)| Some((dir.as_ref()?, source)))
+    }
+
+    /// Finds the first source that has the input of `day`.
+    pub fn resolve(&self, day: u8) -> Result<Source> {
+        if let Some(path) = &self.path {
+            return Ok(Source::Path(path.clone()));
+        }
+        let mut tried = vec![];
+        for (dir, source) in self.dirs() {
+            let path = day_path(dir, day);
+            if path.is_file() {
+                return Ok(source(path));
+            }
+            tried.push(path.display().to_string());
+        }
+        if let (Some(client), Some(cache)) = (&self.fetch, &self.cache_dir) {
+            if client.has_session() {
+                client.clone().cache_dir(Some(cache.clone())).input(day)?;
+                return Ok(Source::Cache(day_path(cache, day)));
+            }
+        }
+        if self.stdin && !io::stdin().is_terminal() {
+            return Ok(Source::Stdin);
+        }
+        bail!("no input for day {day}, tried: {}", tried.join(", "))
+    }
+
+    /// Reads the input of `day`.
+    pub fn load(&self, day: u8) -> Result<String> {
+        self.read(day, &self.resolve(day)?)
+    }
+
+    /// Reads `source` as the input of `day`.
+    pub fn read(&self, day: u8, source: &Source) -> Result<String> {
+        normalise(day, &source.read()?, self.mode).with_context(|| format!("input {source}"))
+    }
+
+    /// Opens the input of `day` for streaming, normalised as it is read, see
+    /// [`Normalised`].
+    pub fn open(&self, day: u8) -> Result<Box<dyn BufRead>> {
+        let reader = self.resolve(day)?.open()?;
+        Ok(Box::new(Normalised::new(day, reader, self.mode)))
+    }
+
+    /// Every input file of `day` in the configured directories, not only
+    /// `input.txt` but any `.txt` file of the day directory.
+    pub fn inputs(&self, day: u8) -> Vec<Source> {
+        let mut inputs = vec![];
+        if let Some(path) = &self.path {
+            inputs.push(Source::Path(path.clone()));
+        }
+        for (dir, source) in self.dirs() {
+            let Ok(entries) = fs::read_dir(dir.join(format!("day{day}"))) else {
+                continue;
+            };
+            let mut paths = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "txt"))
+                .collect::<Vec<PathBuf>>();
+            paths.sort();
+            inputs.extend(paths.into_iter().map(source));
+        }
+        inputs
+    }
+}
+
+/// Where the input of `day` lives inside an input directory.
+pub fn day_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("day{day}")).join("input.txt")
+}
+
+/// `<user cache>/advent-of-code/<year>`.
+pub fn default_cache_dir(year: u16) -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("advent-of-code").join(year.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-input-{name}-{}", std::process::id()));
+        fs::create_dir_all(dir.join("day1")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn resolve_order() -> Result<()> {
+        let input_dir = temp_dir("env");
+        let cache_dir = temp_dir("cache");
+        let bundled_dir = temp_dir("bundled");
+        fs::write(day_path(&cache_dir, 1), "cached")?;
+        fs::create_dir_all(bundled_dir.join("day5"))?;
+        fs::write(day_path(&bundled_dir, 5), "bundled")?;
+        let provider = InputProvider::new(2023)
+            .input_dir(Some(input_dir.clone()))
+            .cache_dir(Some(cache_dir.clone()))
+            .bundled_dir(Some(bundled_dir.clone()))
+            .stdin(false);
+
+        assert_eq!(provider.resolve(1)?, Source::Cache(day_path(&cache_dir, 1)));
+        fs::write(day_path(&input_dir, 1), "private")?;
+        assert_eq!(provider.load(1)?, "private");
+        assert_eq!(
+            provider.open(1)?.lines().next().transpose()?.as_deref(),
+            Some("private")
+        );
+        let explicit = cache_dir.join("day1").join("input.txt");
+        assert_eq!(
+            provider.clone().path(Some(explicit.clone())).resolve(1)?,
+            Source::Path(explicit)
+        );
+        assert!(matches!(provider.resolve(5)?, Source::Bundled(_)));
+
+        fs::remove_dir_all(input_dir)?;
+        fs::remove_dir_all(cache_dir)?;
+        fs::remove_dir_all(bundled_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn missing_input() {
+        let provider = InputProvider::new(2023)
+            .input_dir(None)
+            .cache_dir(None)
+            .bundled_dir(None)
+            .stdin(false);
+        assert!(provider.load(1).is_err());
+    }
+
+    #[test]
+    fn normalised_inputs() -> Result<()> {
+        let input_dir = temp_dir("normalise");
+        fs::write(
+            day_path(&input_dir, 1),
+            "\u{feff}  1abc2\r\n  a1b2c3\r\n\r\n",
+        )?;
+        let provider = InputProvider::new(2023)
+            .input_dir(Some(input_dir.clone()))
+            .stdin(false);
+
+        assert_eq!(provider.load(1)?, "1abc2\na1b2c3\n");
+        let mut streamed = String::new();
+        provider.open(1)?.read_to_string(&mut streamed)?;
+        assert_eq!(streamed, "1abc2\na1b2c3\n");
+        let strict = provider.normalise(Mode::Strict);
+        let error = strict.load(1).unwrap_err();
+        assert_eq!(
+            error.root_cause().to_string(),
+            "day 1 line 1 column 1: expected no byte order mark, found `\\u{feff}`"
+        );
+        assert!(strict.open(1)?.read_to_string(&mut streamed).is_err());
+
+        fs::remove_dir_all(input_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_missing_input() -> Result<()> {
+        let (url, server) = crate::fetch::mock_server(vec![(200, "fetched")]);
+        let cache_dir = temp_dir("fetch");
+        let client = Client::new(2023)
+            .base_url(url)
+            .session(Some("abc123".to_owned()));
+        let provider = InputProvider::new(2023)
+            .input_dir(None)
+            .cache_dir(Some(cache_dir.clone()))
+            .bundled_dir(None)
+            .fetch(Some(client))
+            .stdin(false);
+
+        assert_eq!(
+            provider.resolve(13)?,
+            Source::Cache(day_path(&cache_dir, 13))
+        );
+        assert_eq!(provider.load(13)?, "fetched");
+        assert_eq!(server.join().unwrap().len(), 1);
+        fs::remove_dir_all(cache_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+//! Shared tooling of the Advent of Code solutions of every year.
+//!
+//! A year crate implements [`Solution`](solution::Solution) for each of its
+//! days, parses with [`parsing`], [`grid`] and [`math`], and registers the
+//! days in a [`Calendar`]. The `aoc` runner loads inputs, checks answers and
+//! talks to the website for any registered calendar.
+#![warn(missing_docs)]
+
+pub mod answers;
+pub mod calendar;
+pub mod error;
+pub mod fetch;
+pub mod grid;
+pub mod input;
+pub mod math;
+pub mod normalise;
+pub mod parsing;
+pub mod report;
+pub mod scaffold;
+/// The [`Solution`](solution::Solution) trait implemented by every day.
+pub mod solution;
+pub mod submit;
+
+pub use calendar::Calendar;
@@ -0,0 +1,225 @@
+//! Exact integer arithmetic, instead of floats that lose precision past
+//! 2^53.
+
+use std::{fmt, ops::RangeInclusive};
+
+/// Floor of the square root of `n`.
+pub fn isqrt(n: u128) -> u128 {
+    n.isqrt()
+}
+
+/// An intermediate value of a computation does not fit in its integer type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the arithmetic overflows")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// Addition that returns `None` instead of overflowing, for the integer
+/// types.
+pub trait CheckedAdd: Sized {
+    /// `self + other`, `None` when it overflows.
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! checked_add {
+    ($($int:ty),*) => {
+        $(impl CheckedAdd for $int {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$int>::checked_add(self, other)
+            }
+        })*
+    };
+}
+
+checked_add!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// The integers `x` where `a·x² + b·x + c > 0`, for `a < 0` so they form a
+/// single range. `None` when there is none, or when `a` is not negative.
+///
+/// Fails with [`Overflow`] when `b² - 4·a·c` does not fit in `i128`, which
+/// 64-bit coefficients can already reach.
+pub fn quadratic_positive(
+    a: i128,
+    b: i128,
+    c: i128,
+) -> Result<Option<RangeInclusive<i128>>, Overflow> {
+    if a >= 0 {
+        return Ok(None);
+    }
+    // Horner's form, near the roots `(a·x + b)·x` stays close to `-c`
+    let f = |x: i128| {
+        a.checked_mul(x)
+            .and_then(|ax| ax.checked_add(b))
+            .and_then(|axb| axb.checked_mul(x))
+            .and_then(|axbx| axbx.checked_add(c))
+            .ok_or(Overflow)
+    };
+    // the roots are (b ± √d) / 2|a|
+    let d = b
+        .checked_mul(b)
+        .zip(a.checked_mul(4).and_then(|a4| a4.checked_mul(c)))
+        .and_then(|(b2, ac4)| b2.checked_sub(ac4))
+        .ok_or(Overflow)?;
+    if d <= 0 {
+        return Ok(None);
+    }
+    let root = isqrt(d as u128) as i128;
+    let twice_a = a.checked_mul(-2).ok_or(Overflow)?;
+    let vertex = b.div_euclid(twice_a);
+
+    // `root` is at most 1 below √d, so these are off by one at most
+    let mut lower = (b - root).div_euclid(twice_a);
+    while f(lower - 1)? > 0 {
+        lower -= 1;
+    }
+    while lower <= vertex + 1 && f(lower)? <= 0 {
+        lower += 1;
+    }
+    let mut upper = (b + root).div_euclid(twice_a) + 1;
+    while f(upper + 1)? > 0 {
+        upper += 1;
+    }
+    while upper >= lower && f(upper)? <= 0 {
+        upper -= 1;
+    }
+    Ok((f(lower)? > 0 && lower <= upper).then_some(lower..=upper))
+}
+
+/// Greatest common divisor, `gcd(0, 0)` is 0.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, `None` when it overflows.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Greatest common divisor of every value, 0 for none.
+pub fn gcd_all(values: &[u64]) -> u64 {
+    values.iter().fold(0, |acc, value| gcd(acc, *value))
+}
+
+/// Least common multiple of every value, 1 for none and `None` when it
+/// overflows.
+pub fn lcm_all(values: &[u64]) -> Option<u64> {
+    values.iter().try_fold(1, |acc, value| lcm(acc, *value))
+}
+
+/// `(g, x, y)` with `a·x + b·y = g = gcd(a, b)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a.rem_euclid(b));
+        (g, y, x - a.div_euclid(b) * y)
+    }
+}
+
+/// Solves `x ≡ residue (mod modulus)` for every `(residue, modulus)`, the
+/// moduli need not be coprime. Returns the smallest non negative `x` and the
+/// combined modulus, `None` when the congruences contradict each other or the
+/// modulus overflows.
+pub fn crt(congruences: &[(i64, u64)]) -> Option<(u64, u64)> {
+    // values stay below 2^64, so products of two of them fit in u128
+    let mut x: u128 = 0;
+    let mut modulus: u128 = 1;
+    for &(residue, m) in congruences {
+        if m == 0 {
+            return None;
+        }
+        let m = u128::from(m);
+        let residue = i128::from(residue).rem_euclid(m as i128);
+        let (g, p, _) = extended_gcd(modulus as i128, m as i128);
+        let difference = residue - x as i128;
+        if difference % g != 0 {
+            return None;
+        }
+        let step = m / g as u128;
+        // x + modulus·k ≡ residue (mod m) for k = difference/g · p (mod m/g)
+        let k = (difference / g).rem_euclid(step as i128) as u128
+            * p.rem_euclid(step as i128) as u128
+            % step;
+        x += modulus * k;
+        modulus *= step;
+        if modulus > u128::from(u64::MAX) {
+            return None;
+        }
+        x %= modulus;
+    }
+    Some((x as u64, modulus as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_roots() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        let big = (1u128 << 60) + 1;
+        assert_eq!(isqrt(big * big), big);
+        assert_eq!(isqrt(big * big - 1), big - 1);
+    }
+
+    #[test]
+    fn quadratic() {
+        // -x² + 7x - 9 > 0 for 2..=5
+        assert_eq!(quadratic_positive(-1, 7, -9), Ok(Some(2..=5)));
+        // exact integer roots 10 and 20 are excluded
+        assert_eq!(quadratic_positive(-1, 30, -200), Ok(Some(11..=19)));
+        // -(x - 2^31)² + 1 > 0 only at the vertex, floats round it away
+        let k = 1i128 << 31;
+        assert_eq!(quadratic_positive(-1, 2 * k, 1 - k * k), Ok(Some(k..=k)));
+        assert_eq!(quadratic_positive(-1, 2 * k, -k * k), Ok(None));
+        assert_eq!(quadratic_positive(1, 0, -1), Ok(None));
+        let t = 1i128 << 62;
+        let range = quadratic_positive(-1, t, -(t - 1)).unwrap().unwrap();
+        assert_eq!(range, 2..=t - 2);
+        let t = i128::from(u64::MAX);
+        let range = quadratic_positive(-1, t >> 1, -1).unwrap().unwrap();
+        assert_eq!(range, 1..=(t >> 1) - 1);
+        assert_eq!(quadratic_positive(-1, t, -1), Err(Overflow));
+        assert_eq!(quadratic_positive(i128::MIN, 0, 1), Err(Overflow));
+    }
+
+    #[test]
+    fn multiples() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd_all(&[12, 18, 8]), 2);
+        assert_eq!(gcd_all(&[]), 0);
+        assert_eq!(lcm_all(&[4, 6, 10]), Some(60));
+        assert_eq!(lcm_all(&[]), Some(1));
+        assert_eq!(lcm_all(&[u64::MAX, u64::MAX - 1]), None);
+        assert_eq!(lcm(0, 5), Some(0));
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(-1, 5)]), Some((4, 5)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(0, u64::MAX), (1, u64::MAX - 1)]), None);
+        let big = u64::MAX - 58; // the largest 64 bit prime
+        assert_eq!(crt(&[(-1, big)]), Some((big - 1, big)));
+        let (x, m) = crt(&[(5, 1 << 32), (7, (1 << 31) - 1)]).unwrap();
+        assert_eq!((x % (1 << 32), x % ((1 << 31) - 1)), (5, 7));
+        assert_eq!(m, (1 << 63) - (1 << 32));
+    }
+}
@@ -0,0 +1,336 @@
+//! Normalisation of the puzzle inputs before any solver sees them.
+//!
+//! An input saved by a browser or an editor may start with a byte order mark,
+//! end its lines with CRLF, keep trailing spaces or blank lines, or be
+//! indented as a whole when pasted from somewhere else. [`Mode::Repair`]
+//! removes all of it, [`Mode::Strict`] rejects it with the line and column.
+//! A well formed input, including its final newline, is left as is.
+
+use std::io::{self, BufRead, Read};
+
+use crate::error::ParseError;
+
+/// The byte order mark some editors write at the start of a file.
+const BOM: char = '\u{feff}';
+
+/// The whitespace removed at the start and at the end of the lines.
+const BLANKS: [char; 2] = [' ', '\t'];
+
+/// What to do with the formatting that is not part of the puzzle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Remove it.
+    #[default]
+    Repair,
+    /// Fail on it.
+    Strict,
+}
+
+/// Normalises the whole `input` of `day`, the indentation shared by all of
+/// its lines included.
+pub fn normalise(day: u8, input: &str, mode: Mode) -> Result<String, ParseError> {
+    let mut reader = Normalised::new(day, input.as_bytes(), mode);
+    if mode == Mode::Repair {
+        reader.indent = Some(common_indent(input).to_owned());
+    }
+    let mut output = String::with_capacity(input.len());
+    match reader.read_to_string(&mut output) {
+        Ok(_) => Ok(output),
+        // a `str` is valid UTF-8, only the rejections can fail the read
+        Err(e) => Err(*e
+            .into_inner()
+            .and_then(|e| e.downcast::<ParseError>().ok())
+            .expect("reading a str only fails on a rejection")),
+    }
+}
+
+/// The indentation of every line that is not blank.
+fn common_indent(input: &str) -> &str {
+    let input = input.strip_prefix(BOM).unwrap_or(input);
+    input
+        .lines()
+        .map(|line| line.trim_end_matches('\r').trim_end_matches(BLANKS))
+        .filter(|line| !line.is_empty())
+        .map(leading_blanks)
+        .reduce(|indent, line| &indent[..shared(indent, line)])
+        .unwrap_or_default()
+}
+
+/// The blanks at the start of `line`.
+fn leading_blanks(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches(BLANKS).len()]
+}
+
+/// Length of the prefix shared by two indentations.
+fn shared(indent: &str, other: &str) -> usize {
+    indent
+        .bytes()
+        .zip(other.bytes())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// Normalises a line of `day`, given without its `\n`, and repairs its
+/// `indent`; the byte order mark is only looked for on the first line.
+fn line<'a>(
+    day: u8,
+    number: usize,
+    text: &'a str,
+    indent: &str,
+    mode: Mode,
+) -> Result<&'a str, ParseError> {
+    let reject = |column: usize, found: &str, message: &str| {
+        let found = found.escape_debug().to_string();
+        Err(ParseError::new(day, number, column, found, message))
+    };
+    let mut text = text;
+    if number == 1 {
+        if let Some(rest) = text.strip_prefix(BOM) {
+            if mode == Mode::Strict {
+                return reject(1, &BOM.to_string(), "expected no byte order mark");
+            }
+            text = rest;
+        }
+    }
+    if let Some(rest) = text.strip_suffix('\r') {
+        if mode == Mode::Strict {
+            let column = rest.chars().count() + 1;
+            return reject(column, "\r", "expected the line to end with `\\n`");
+        }
+        text = rest;
+    }
+    let trimmed = text.trim_end_matches(BLANKS);
+    if trimmed.len() < text.len() {
+        if mode == Mode::Strict {
+            let column = trimmed.chars().count() + 1;
+            return reject(
+                column,
+                &text[trimmed.len()..],
+                "expected no trailing whitespace",
+            );
+        }
+        text = trimmed;
+    }
+    Ok(text.strip_prefix(indent).unwrap_or(text))
+}
+
+/// A reader that normalises the lines of another one as they are read.
+///
+/// Streaming cannot know the indentation shared by all the lines before the
+/// last one: the lines lose the indentation of the first line that is not
+/// blank, or as much of it as they share with the lines before them. This
+/// only differs from [`normalise`] when the first line is indented more than
+/// a later one. [`Mode::Strict`] rejects an indented first line, streamed or
+/// not. Rejections are [`io::ErrorKind::InvalidData`] errors wrapping the
+/// [`ParseError`].
+#[derive(Debug)]
+pub struct Normalised<R> {
+    reader: R,
+    day: u8,
+    mode: Mode,
+    /// The indentation shared by every line, when known before reading.
+    indent: Option<String>,
+    /// Otherwise the indentation shared by the lines read so far.
+    streamed: Option<String>,
+    /// Lines read so far.
+    number: usize,
+    /// The line being read, with its ending.
+    line: String,
+    /// Blank lines held back until a line follows them.
+    blanks: usize,
+    /// The normalised lines ready to be read.
+    output: Vec<u8>,
+    position: usize,
+}
+
+impl<R: BufRead> Normalised<R> {
+    /// Normalises the input of `day` read from `reader`.
+    pub fn new(day: u8, reader: R, mode: Mode) -> Self {
+        Normalised {
+            reader,
+            day,
+            mode,
+            indent: None,
+            streamed: None,
+            number: 0,
+            line: String::new(),
+            blanks: 0,
+            output: vec![],
+            position: 0,
+        }
+    }
+}
+
+/// An error of the reader carrying a rejection.
+fn invalid(error: ParseError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+impl<R: BufRead> BufRead for Normalised<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.position == self.output.len() {
+            self.output.clear();
+            self.position = 0;
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                if self.blanks > 0 && self.mode == Mode::Strict {
+                    let first = self.number + 1 - self.blanks;
+                    self.blanks = 0;
+                    return Err(invalid(ParseError::new(
+                        self.day,
+                        first,
+                        1,
+                        "\\n",
+                        "expected the input to end after its last line",
+                    )));
+                }
+                break;
+            }
+            self.number += 1;
+            let ended = self.line.ends_with('\n');
+            let text = self.line.strip_suffix('\n').unwrap_or(&self.line);
+            let indent = self.indent.as_deref().unwrap_or_default();
+            let mut text = line(self.day, self.number, text, indent, self.mode).map_err(invalid)?;
+            if text.is_empty() {
+                self.blanks += 1;
+                continue;
+            }
+            if self.indent.is_none() {
+                let leading = leading_blanks(text);
+                if self.streamed.is_none() && !leading.is_empty() && self.mode == Mode::Strict {
+                    return Err(invalid(ParseError::new(
+                        self.day,
+                        self.number,
+                        1,
+                        leading.escape_debug().to_string(),
+                        "expected the first line not to be indented",
+                    )));
+                }
+                let indent = self.streamed.get_or_insert_with(|| leading.to_owned());
+                indent.truncate(shared(indent, leading));
+                text = &text[indent.len()..];
+            }
+            self.output.resize(self.blanks, b'\n');
+            self.blanks = 0;
+            self.output.extend_from_slice(text.as_bytes());
+            if ended {
+                self.output.push(b'\n');
+            }
+        }
+        Ok(&self.output[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.output.len());
+    }
+}
+
+impl<R: BufRead> Read for Normalised<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let amount = available.len().min(buf.len());
+        buf[..amount].copy_from_slice(&available[..amount]);
+        self.consume(amount);
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{InputError, Line};
+
+    #[test]
+    fn repair() {
+        let input = "\u{feff}  Game 1: 3 blue \r\n\r\n    Game 2: 4 red\t\r\n  \r\n\n";
+        assert_eq!(
+            normalise(2, input, Mode::Repair).unwrap(),
+            "Game 1: 3 blue\n\n  Game 2: 4 red\n"
+        );
+        assert_eq!(normalise(2, "a\n b", Mode::Repair).unwrap(), "a\n b");
+        assert_eq!(normalise(2, "", Mode::Repair).unwrap(), "");
+    }
+
+    #[test]
+    fn well_formed_unchanged() {
+        for input in ["1abc2\npqr3stu8vwx\n", "seeds: 79\n\n  a\nb", "x"] {
+            assert_eq!(normalise(1, input, Mode::Repair).unwrap(), input);
+            assert_eq!(normalise(1, input, Mode::Strict).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn strict() {
+        let cases = [
+            (
+                "\u{feff}a\n",
+                ParseError::new(3, 1, 1, "\\u{feff}", "expected no byte order mark"),
+            ),
+            (
+                "a\nbc\r\n",
+                ParseError::new(3, 2, 3, "\\r", "expected the line to end with `\\n`"),
+            ),
+            (
+                "ab \t\n",
+                ParseError::new(3, 1, 3, " \\t", "expected no trailing whitespace"),
+            ),
+            (
+                "\n  a\n  b\n",
+                ParseError::new(3, 2, 1, "  ", "expected the first line not to be indented"),
+            ),
+            (
+                "a\n\n\n",
+                ParseError::new(
+                    3,
+                    2,
+                    1,
+                    "\\n",
+                    "expected the input to end after its last line",
+                ),
+            ),
+        ];
+        for (input, error) in cases {
+            assert_eq!(normalise(3, input, Mode::Strict), Err(error), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn streaming() {
+        let input = "\u{feff}a \r\n\n  b\r\n\n\n";
+        let mut output = String::new();
+        Normalised::new(4, input.as_bytes(), Mode::Repair)
+            .read_to_string(&mut output)
+            .unwrap();
+        assert_eq!(output, normalise(4, input, Mode::Repair).unwrap());
+
+        let reader = Normalised::new(4, "a\nb\r\n".as_bytes(), Mode::Strict);
+        let error = Line::read_each(4, reader, |_| Ok(()));
+        assert!(matches!(error, Err(InputError::Parse(e)) if e.line == 2 && e.column == 2));
+    }
+
+    #[test]
+    fn streaming_indentation() {
+        let read = |input: &str, mode| {
+            let mut output = String::new();
+            Normalised::new(5, input.as_bytes(), mode)
+                .read_to_string(&mut output)
+                .map(|_| output)
+                .map_err(|e| InputError::from(e).to_string())
+        };
+        for input in [
+            "\n  a\n    b\n\n  c\n",
+            "\ta\n\t\tb",
+            "a\n  b",
+            "  a\n  b\n",
+        ] {
+            let expected = normalise(5, input, Mode::Repair).unwrap();
+            assert_eq!(read(input, Mode::Repair), Ok(expected), "{input:?}");
+            let expected = normalise(5, input, Mode::Strict).map_err(|e| e.to_string());
+            assert_eq!(read(input, Mode::Strict), expected, "{input:?}");
+        }
+        // only the first line tells the indentation to remove
+        assert_eq!(read("    a\n  b\n", Mode::Repair).unwrap(), "a\nb\n");
+        assert_eq!(read("  a\nb\n  c", Mode::Repair).unwrap(), "a\nb\n  c");
+        assert!(read("  a\nb\n  c", Mode::Strict).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! nom combinators for the puzzle formats.
+//!
+//! The combinators work on a single line; [`parse_line`] and [`parse_lines`]
+//! run them on whole lines and turn their errors into [`ParseError`]s pointing
+//! at the token where parsing stopped.
+
+use std::str::FromStr;
+
+use nom::{
+    bytes::complete::{tag, take_till1},
+    character::complete::{anychar, char, digit1, one_of, space0, space1},
+    combinator::{all_consuming, map_opt, map_res, opt, recognize},
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+};
+
+use crate::error::{Line, ParseError};
+
+/// Result of the combinators, parsing `&str`.
+pub type IResult<'a, O> = nom::IResult<&'a str, O>;
+
+/// An unsigned integer: `42`.
+pub fn unsigned<T: FromStr>(input: &str) -> IResult<'_, T> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// An integer with an optional sign: `-42`.
+pub fn signed<T: FromStr>(input: &str) -> IResult<'_, T> {
+    map_res(recognize(pair(opt(one_of("+-")), digit1)), str::parse)(input)
+}
+
+/// At least one `item` separated by spaces, with any padding around them.
+pub fn list<'a, O>(
+    item: impl FnMut(&'a str) -> IResult<'a, O>,
+) -> impl FnMut(&'a str) -> IResult<'a, Vec<O>> {
+    delimited(space0, separated_list1(space1, item), space0)
+}
+
+/// Space separated unsigned integers: `79 14  55 13`.
+pub fn unsigned_list<T: FromStr>(input: &str) -> IResult<'_, Vec<T>> {
+    list(unsigned)(input)
+}
+
+/// Space separated signed integers: `10 -3 0`.
+pub fn signed_list<T: FromStr>(input: &str) -> IResult<'_, Vec<T>> {
+    list(signed)(input)
+}
+
+/// The `values` of a `key: values` line.
+pub fn key_values<'a, O>(
+    key: &'a str,
+    values: impl FnMut(&'a str) -> IResult<'a, O>,
+) -> impl FnMut(&'a str) -> IResult<'a, O> {
+    preceded(tuple((space0, tag(key), char(':'), space0)), values)
+}
+
+/// The label of a block header, `seed-to-soil` of `seed-to-soil map:` with
+/// `suffix` ` map:`.
+pub fn header<'a>(suffix: &'a str) -> impl FnMut(&'a str) -> IResult<'a, &'a str> {
+    delimited(
+        space0,
+        terminated(
+            take_till1(|c: char| c.is_whitespace() || c == ':'),
+            tag(suffix),
+        ),
+        space0,
+    )
+}
+
+/// A row of cells, one per character.
+pub fn cells<'a, T>(
+    cell: impl Fn(char) -> Option<T>,
+) -> impl FnMut(&'a str) -> IResult<'a, Vec<T>> {
+    many0(map_opt(anychar, cell))
+}
+
+/// The lines of `input` grouped in sections separated by blank lines.
+pub fn sections(day: u8, input: &str) -> Vec<Vec<Line<'_>>> {
+    let mut sections = vec![];
+    let mut section = vec![];
+    for line in Line::lines(day, input) {
+        if line.text.trim().is_empty() {
+            if !section.is_empty() {
+                sections.push(section);
+                section = vec![];
+            }
+        } else {
+            section.push(line);
+        }
+    }
+    if !section.is_empty() {
+        sections.push(section);
+    }
+    sections
+}
+
+/// Runs `parser` on the whole `line`, an error expects `what`.
+pub fn parse_line<'a, O>(
+    line: &Line<'a>,
+    what: &str,
+    parser: impl FnMut(&'a str) -> IResult<'a, O>,
+) -> Result<O, ParseError> {
+    match all_consuming(parser)(line.text) {
+        Ok((_, value)) => Ok(value),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(error(line, e.input, what)),
+        Err(nom::Err::Incomplete(_)) => Err(error(line, "", what)),
+    }
+}
+
+/// Runs `parser` on every line of `input`.
+pub fn parse_lines<'a, O>(
+    day: u8,
+    input: &'a str,
+    what: &str,
+    mut parser: impl FnMut(&'a str) -> IResult<'a, O>,
+) -> Result<Vec<O>, ParseError> {
+    Line::lines(day, input)
+        .map(|line| parse_line(&line, what, &mut parser))
+        .collect()
+}
+
+/// An error at the token starting at `at`, the rest of the line where a
+/// parser stopped.
+fn error(line: &Line<'_>, at: &str, what: &str) -> ParseError {
+    let at = at.trim_start();
+    let end = match at.find(|c: char| c.is_whitespace() || ",;:()|=".contains(c)) {
+        Some(0) => at.chars().next().map_or(0, char::len_utf8),
+        Some(end) => end,
+        None => at.len(),
+    };
+    line.error(&at[..end], format!("expected {what}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str) -> Line<'_> {
+        Line::lines(0, text).next().unwrap()
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            unsigned_list::<u64>("  79 14  55 13 "),
+            Ok(("", vec![79, 14, 55, 13]))
+        );
+        assert_eq!(signed_list::<i64>("10 -3 +0"), Ok(("", vec![10, -3, 0])));
+        assert!(unsigned::<u8>("256").is_err());
+        assert!(signed::<i64>("-").is_err());
+    }
+
+    #[test]
+    fn keys_and_headers() {
+        let time = line("Time:      7  15   30");
+        let values = parse_line(&time, "times", key_values("Time", unsigned_list::<u32>));
+        assert_eq!(values, Ok(vec![7, 15, 30]));
+        assert_eq!(
+            header(" map:")("seed-to-soil map:"),
+            Ok(("", "seed-to-soil"))
+        );
+        assert_eq!(
+            cells(|c| (c == '#').then_some(1))("##."),
+            Ok((".", vec![1, 1]))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let seeds = line("seeds: 79 x 3");
+        let error = parse_line(
+            &seeds,
+            "seed numbers",
+            key_values("seeds", unsigned_list::<u64>),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(0, 1, 11, "x", "expected seed numbers")
+        );
+        let error = parse_line(
+            &line("seeds:"),
+            "seeds",
+            key_values("seeds", unsigned_list::<u64>),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 0 line 1 column 7: expected seeds (unexpected end of input)"
+        );
+        let error = parse_lines(0, "1 2\n3, 4", "numbers", unsigned_list::<u8>).unwrap_err();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 2, ","));
+    }
+
+    #[test]
+    fn split_sections() {
+        let sections = sections(0, "a\nb\n\n  \nc\n");
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[1][0].number, 5);
+    }
+}
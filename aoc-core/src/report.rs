@@ -0,0 +1,230 @@
+//! Runs every registered day and part of a year on its input, for
+//! `aoc run --all`, and every implementation of a day, for `aoc compare`.
+
+use std::time::{Duration, Instant};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::{Serialize, Serializer};
+
+use crate::{
+    answers::{input_hash, AnswerStore, Status},
+    input::InputProvider,
+    solution::Solver,
+    Calendar,
+};
+
+/// The outcome of one day and part.
+#[derive(Debug, Clone, Serialize)]
+pub struct Run {
+    /// Year of the puzzle.
+    pub year: u16,
+    /// Day of the puzzle.
+    pub day: u8,
+    /// Part of the puzzle.
+    pub part: u8,
+    /// What the solver answered.
+    pub answer: Option<String>,
+    /// The stored answer, if any.
+    pub expected: Option<String>,
+    /// How both compare.
+    pub status: Status,
+    /// Time spent parsing the input.
+    #[serde(rename = "parse_us", serialize_with = "micros")]
+    pub parse: Duration,
+    /// Time spent solving the parsed input.
+    #[serde(rename = "solve_us", serialize_with = "micros")]
+    pub solve: Duration,
+}
+
+/// Every run of [`run_all`] and the wall time they took together.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// One run per day and part, in calendar order.
+    pub runs: Vec<Run>,
+    /// Time from the first run to the last one.
+    #[serde(rename = "total_us", serialize_with = "micros")]
+    pub total: Duration,
+}
+
+impl Report {
+    /// Runs that failed or gave an unexpected answer.
+    pub fn failures(&self) -> usize {
+        self.runs
+            .iter()
+            .filter(|run| matches!(run.status, Status::Mismatch | Status::Failed(_)))
+            .count()
+    }
+}
+
+fn micros<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_micros())
+}
+
+/// Solves both parts of every day of the calendar on the input the provider
+/// resolves, on all cores when `parallel`.
+pub fn run_all(
+    store: &AnswerStore,
+    calendar: &Calendar,
+    provider: &InputProvider,
+    parallel: bool,
+) -> Report {
+    let jobs = calendar
+        .days
+        .iter()
+        .flat_map(|solver| [(*solver, 1), (*solver, 2)])
+        .collect::<Vec<(&dyn Solver, u8)>>();
+    let start = Instant::now();
+    let run = |(solver, part)| run_one(store, calendar.year, provider, solver, part);
+    let runs = if parallel {
+        jobs.into_par_iter().map(run).collect()
+    } else {
+        jobs.into_iter().map(run).collect()
+    };
+    Report {
+        runs,
+        total: start.elapsed(),
+    }
+}
+
+fn run_one(
+    store: &AnswerStore,
+    year: u16,
+    provider: &InputProvider,
+    solver: &dyn Solver,
+    part: u8,
+) -> Run {
+    let day = solver.day();
+    let mut run = Run {
+        year,
+        day,
+        part,
+        answer: None,
+        expected: None,
+        status: Status::Unknown,
+        parse: Duration::ZERO,
+        solve: Duration::ZERO,
+    };
+    let input = match provider.load(day) {
+        Ok(input) => input,
+        Err(e) => {
+            run.status = Status::Failed(e.to_string());
+            return run;
+        }
+    };
+    run.expected = store
+        .expected(year, day, &input_hash(&input), part)
+        .map(str::to_owned);
+    match solver.solve_timed(&input, part) {
+        Ok(timed) => {
+            let answer = timed.answer.to_string();
+            run.status = match &run.expected {
+                Some(expected) if *expected == answer => Status::Ok,
+                Some(_) => Status::Mismatch,
+                None => Status::Unknown,
+            };
+            run.answer = Some(answer);
+            run.parse = timed.parse;
+            run.solve = timed.solve;
+        }
+        Err(e) => run.status = Status::Failed(e.to_string()),
+    }
+    run
+}
+
+/// The outcome of one implementation of a part, see [`compare`].
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// Part of the puzzle.
+    pub part: u8,
+    /// Name of the implementation.
+    pub implementation: &'static str,
+    /// What it answered, or why it failed.
+    pub answer: Result<String, String>,
+    /// Whether it answered like the default implementation.
+    pub agrees: bool,
+    /// Time it took, parsing included.
+    pub time: Duration,
+}
+
+/// Runs every implementation of `part` on `input`, the default one first,
+/// and compares their answers with its answer.
+pub fn compare(solver: &dyn Solver, input: &str, part: u8) -> Vec<Comparison> {
+    let mut comparisons = solver
+        .implementation_names(part)
+        .into_iter()
+        .map(|implementation| {
+            let start = Instant::now();
+            let answer = solver.solve_with(implementation, input, part);
+            Comparison {
+                part,
+                implementation,
+                answer: answer.map(|a| a.to_string()).map_err(|e| e.to_string()),
+                agrees: false,
+                time: start.elapsed(),
+            }
+        })
+        .collect::<Vec<Comparison>>();
+    let reference = comparisons[0].answer.clone();
+    for comparison in &mut comparisons {
+        comparison.agrees = reference.is_ok() && comparison.answer == reference;
+    }
+    comparisons
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{
+        calendar::TEST_CALENDAR,
+        solution::{Answer, DEFAULT_IMPLEMENTATION},
+    };
+
+    #[test]
+    fn run_all_sample() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc-report-{}", std::process::id()));
+        fs::create_dir_all(dir.join("day1"))?;
+        let input = "1\n2\n3";
+        fs::write(dir.join("day1").join("input.txt"), input)?;
+        let provider = InputProvider::new(2023)
+            .input_dir(None)
+            .cache_dir(None)
+            .bundled_dir(Some(dir.clone()))
+            .stdin(false);
+        let mut store = AnswerStore::default();
+        store.record(2023, 1, &input_hash(input), 1, &Answer::Unsigned(6));
+
+        let report = run_all(&store, &TEST_CALENDAR, &provider, true);
+        fs::remove_dir_all(dir)?;
+        assert_eq!(report.runs.len(), 4);
+        assert_eq!(report.runs[0].status, Status::Ok);
+        assert_eq!(report.runs[1].status, Status::Unknown);
+        assert!(matches!(report.runs[2].status, Status::Failed(_)));
+        assert_eq!(report.failures(), report.runs.len() - 2);
+
+        let json = serde_json::to_value(&report)?;
+        assert_eq!(json["runs"][0]["answer"], "6");
+        assert_eq!(json["runs"][0]["status"], "ok");
+        assert!(json["runs"][0]["solve_us"].is_u64());
+        Ok(())
+    }
+
+    #[test]
+    fn compare_implementations() {
+        let solver = TEST_CALENDAR.get(1).unwrap();
+        let comparisons = compare(solver, "1\n2\n3", 1)
+            .into_iter()
+            .map(|c| (c.implementation, c.answer, c.agrees))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comparisons,
+            [
+                (DEFAULT_IMPLEMENTATION, Ok("6".to_owned()), true),
+                ("reversed", Ok("6".to_owned()), true),
+                ("wrong", Ok("3".to_owned()), false),
+            ]
+        );
+        assert!(compare(solver, "x", 1).iter().all(|c| !c.agrees));
+    }
+}
@@ -0,0 +1,163 @@
+//! Generates the module of a new day from `templates/day.rs.tmpl`, and its
+//! example fixture from `templates/example.*.tmpl`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+const TEMPLATE: &str = include_str!("../templates/day.rs.tmpl");
+const EXAMPLE: &str = include_str!("../templates/example.txt.tmpl");
+const EXAMPLE_ANSWERS: &str = include_str!("../templates/example.toml.tmpl");
+
+/// The `mod.rs` of `day`.
+pub fn render(day: u8) -> String {
+    fill(TEMPLATE, day)
+}
+
+/// `template` for `day`.
+fn fill(template: &str, day: u8) -> String {
+    template.replace("{{day}}", &day.to_string())
+}
+
+/// Creates `src/dayN/mod.rs`, an empty `src/dayN/input.txt` and a
+/// `fixtures/dayN/example.txt` with its `example.toml` that the scaffolded
+/// solver passes, under the crate `root`, and registers the module in
+/// `src/lib.rs`. Returns the created files; an existing day is left untouched.
+pub fn scaffold(root: &Path, day: u8) -> Result<Vec<PathBuf>> {
+    let src = root.join("src");
+    let dir = src.join(format!("day{day}"));
+    let fixtures = root.join("fixtures").join(format!("day{day}"));
+    for dir in [&dir, &fixtures] {
+        if dir.exists() {
+            bail!("{} already exists", dir.display());
+        }
+    }
+    let lib_path = src.join("lib.rs");
+    let lib =
+        fs::read_to_string(&lib_path).with_context(|| format!("reading {}", lib_path.display()))?;
+    let lib = register(&lib, day)?;
+
+    fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+    let module = dir.join("mod.rs");
+    fs::write(&module, render(day))?;
+    let input = dir.join("input.txt");
+    fs::write(&input, "")?;
+    fs::create_dir_all(&fixtures).with_context(|| format!("creating {}", fixtures.display()))?;
+    let example = fixtures.join("example.txt");
+    fs::write(&example, fill(EXAMPLE, day))?;
+    let answers = fixtures.join("example.toml");
+    fs::write(&answers, fill(EXAMPLE_ANSWERS, day))?;
+    fs::write(&lib_path, lib)?;
+    Ok(vec![module, input, example, answers, lib_path])
+}
+
+/// Adds `pub mod dayN;` and `&dayN::DayN` to the source of `lib.rs`, in the
+/// order rustfmt keeps them.
+fn register(lib: &str, day: u8) -> Result<String> {
+    let module = format!("pub mod day{day};");
+    let entry = format!("    &day{day}::Day{day},");
+    if lib.lines().any(|line| line == module) {
+        bail!("day{day} is already registered in lib.rs");
+    }
+    let mut lines = lib.lines().map(str::to_owned).collect::<Vec<String>>();
+
+    let modules = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| Some((i, line.strip_prefix("pub mod ")?.strip_suffix(';')?)))
+        .collect::<Vec<(usize, &str)>>();
+    let Some(&(last, _)) = modules.last() else {
+        bail!("no modules in lib.rs");
+    };
+    let name = format!("day{day}");
+    let at = modules
+        .iter()
+        .find(|(_, other)| **other > *name.as_str())
+        .map_or(last + 1, |(i, _)| *i);
+    // keep a module doc comment with its module
+    let at = (0..at)
+        .rev()
+        .take_while(|i| lines[*i].starts_with("///"))
+        .last()
+        .unwrap_or(at);
+    lines.insert(at, module);
+
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("pub static DAYS"))
+        .context("no DAYS in lib.rs")?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .position(|line| line == "];")
+            .context("unterminated DAYS in lib.rs")?;
+    let at = (start + 1..end)
+        .find(|i| {
+            lines[*i]
+                .trim()
+                .strip_prefix("&day")
+                .and_then(|rest| rest.split_once("::"))
+                .and_then(|(n, _)| n.parse::<u8>().ok())
+                .is_some_and(|n| n > day)
+        })
+        .unwrap_or(end);
+    lines.insert(at, entry);
+
+    let mut lib = lines.join("\n");
+    lib.push('\n');
+    Ok(lib)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_template() {
+        let module = render(13);
+        assert!(module.starts_with("//! Day 13: TODO."));
+        assert!(module.contains("pub fn day13_part1(input: String)"));
+        assert!(module.contains("test_input(13)?"));
+        assert!(!module.contains("{{"));
+    }
+
+    #[test]
+    fn scaffold_day() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(
+            root.join("src").join("lib.rs"),
+            "pub mod day1;\npub mod day2;\npub mod error;\n/// Docs.\npub mod solution;\n\n\
+             pub static DAYS: &[&dyn Solver] = &[\n    &day1::Day1,\n    &day2::Day2,\n];\n",
+        )?;
+
+        scaffold(&root, 13)?;
+        scaffold(&root, 3)?;
+        scaffold(&root, 25)?;
+        let lib = fs::read_to_string(root.join("src").join("lib.rs"))?;
+        assert_eq!(
+            lib,
+            "pub mod day1;\npub mod day13;\npub mod day2;\npub mod day25;\npub mod day3;\n\
+             pub mod error;\n/// Docs.\npub mod solution;\n\n\
+             pub static DAYS: &[&dyn Solver] = &[\n    &day1::Day1,\n    &day2::Day2,\n    \
+             &day3::Day3,\n    &day13::Day13,\n    &day25::Day25,\n];\n"
+        );
+        assert_eq!(fs::read_to_string(root.join("src/day13/input.txt"))?, "");
+        assert_eq!(
+            fs::read_to_string(root.join("fixtures/day13/example.txt"))?,
+            "TODO: the example of day 13\n"
+        );
+        assert!(
+            fs::read_to_string(root.join("fixtures/day13/example.toml"))?.contains("part1 = \"1\"")
+        );
+        assert!(scaffold(&root, 13).is_err());
+        assert!(scaffold(&root, 1).is_err());
+        assert!(!root.join("src/day1").exists());
+
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    fmt,
+    io::BufRead,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use tracing::{debug_span, info_span};
+
+use crate::error::ParseError;
+
+/// The answer to one part of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    /// A signed integer.
+    Signed(i64),
+    /// An unsigned integer.
+    Unsigned(u64),
+    /// A text answer.
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => write!(f, "{n}"),
+            Answer::Unsigned(n) => write!(f, "{n}"),
+            Answer::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        Answer::Signed(value)
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(value: u32) -> Self {
+        Answer::Unsigned(value.into())
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        Answer::Unsigned(value)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Unsigned(value as u64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_owned())
+    }
+}
+
+/// An answer and how long it took to get it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timed {
+    /// The answer of the part.
+    pub answer: Answer,
+    /// Time spent parsing the input.
+    pub parse: Duration,
+    /// Time spent solving the parsed input.
+    pub solve: Duration,
+}
+
+/// Name of the implementation made of [`Solution::part1`] and
+/// [`Solution::part2`].
+pub const DEFAULT_IMPLEMENTATION: &str = "default";
+
+/// Another way of solving a part, see [`Solution::IMPLEMENTATIONS`].
+#[derive(Debug, Clone, Copy)]
+pub struct Implementation {
+    /// Name to pick it with `--impl`.
+    pub name: &'static str,
+    /// The part it solves.
+    pub part: u8,
+    /// Solves the part from the whole input.
+    pub solve: fn(&str) -> Result<Answer>,
+}
+
+/// A day of the calendar: how to parse its input and solve both parts.
+pub trait Solution {
+    /// Day of the calendar.
+    const DAY: u8;
+    /// Parsed input shared by both parts.
+    type Input;
+    /// Other implementations of the parts, run against the default one by
+    /// `aoc compare`.
+    const IMPLEMENTATIONS: &'static [Implementation] = &[];
+
+    /// Parses the puzzle input.
+    fn parse(input: &str) -> Result<Self::Input>;
+    /// Solves part 1.
+    fn part1(input: &Self::Input) -> Result<Answer>;
+    /// Solves part 2.
+    fn part2(input: &Self::Input) -> Result<Answer>;
+
+    /// Solves `part` reading the input from `reader`. This reads the whole
+    /// input first, the line oriented days read one line at a time instead.
+    fn solve_reader(reader: &mut dyn BufRead, part: u8) -> Result<Answer> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        let input = Self::parse(&input)?;
+        match part {
+            1 => Self::part1(&input),
+            2 => Self::part2(&input),
+            _ => bail!("part {part} does not exist"),
+        }
+    }
+
+    /// Checks what the solvers assume of the input beyond its syntax, one
+    /// error per violation, and fails when the input does not parse. By
+    /// default it is only parsed.
+    fn validate(input: &str) -> Result<Vec<ParseError>> {
+        Self::parse(input)?;
+        Ok(vec![])
+    }
+}
+
+/// Object safe view of a [`Solution`], used by the registry.
+pub trait Solver: Sync {
+    /// Day of the calendar.
+    fn day(&self) -> u8;
+    /// Parses the input and solves part 1.
+    fn part1(&self, input: &str) -> Result<Answer>;
+    /// Parses the input and solves part 2.
+    fn part2(&self, input: &str) -> Result<Answer>;
+
+    /// Parses the input and solves `part`.
+    fn solve(&self, input: &str, part: u8) -> Result<Answer> {
+        match part {
+            1 => self.part1(input),
+            2 => self.part2(input),
+            _ => bail!("part {part} does not exist"),
+        }
+    }
+
+    /// Solves `part` reading the input from `reader`, see
+    /// [`Solution::solve_reader`].
+    fn solve_reader(&self, reader: &mut dyn BufRead, part: u8) -> Result<Answer>;
+
+    /// Like [`Solver::solve`], timing the parsing and the solving apart.
+    fn solve_timed(&self, input: &str, part: u8) -> Result<Timed>;
+
+    /// The violations of the assumptions of the solvers, see
+    /// [`Solution::validate`].
+    fn validate(&self, input: &str) -> Result<Vec<ParseError>>;
+
+    /// The other implementations, see [`Solution::IMPLEMENTATIONS`].
+    fn implementations(&self) -> &'static [Implementation];
+
+    /// Names of the implementations of `part`, the default one first.
+    fn implementation_names(&self, part: u8) -> Vec<&'static str> {
+        let others = self.implementations().iter().filter(|i| i.part == part);
+        [DEFAULT_IMPLEMENTATION]
+            .into_iter()
+            .chain(others.map(|i| i.name))
+            .collect()
+    }
+
+    /// Solves `part` with the implementation called `name`.
+    fn solve_with(&self, name: &str, input: &str, part: u8) -> Result<Answer> {
+        if name == DEFAULT_IMPLEMENTATION {
+            return self.solve(input, part);
+        }
+        let Some(implementation) = self
+            .implementations()
+            .iter()
+            .find(|i| i.name == name && i.part == part)
+        else {
+            bail!(
+                "day {} has no `{name}` implementation of part {part}",
+                self.day()
+            );
+        };
+        let _span = info_span!("solve", day = self.day(), part, implementation = name).entered();
+        (implementation.solve)(input)
+    }
+}
+
+impl<S: Solution + Sync> Solver for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer> {
+        let _span = info_span!("solve", day = S::DAY, part = 1).entered();
+        S::part1(&S::parse(input)?)
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer> {
+        let _span = info_span!("solve", day = S::DAY, part = 2).entered();
+        S::part2(&S::parse(input)?)
+    }
+
+    fn solve_reader(&self, reader: &mut dyn BufRead, part: u8) -> Result<Answer> {
+        let _span = info_span!("solve", day = S::DAY, part).entered();
+        S::solve_reader(reader, part)
+    }
+
+    fn solve_timed(&self, input: &str, part: u8) -> Result<Timed> {
+        let _span = info_span!("solve", day = S::DAY, part).entered();
+        let start = Instant::now();
+        let parsed = debug_span!("parse").in_scope(|| S::parse(input))?;
+        let parse = start.elapsed();
+        let start = Instant::now();
+        let answer = match part {
+            1 => S::part1(&parsed)?,
+            2 => S::part2(&parsed)?,
+            _ => bail!("part {part} does not exist"),
+        };
+        Ok(Timed {
+            answer,
+            parse,
+            solve: start.elapsed(),
+        })
+    }
+
+    fn validate(&self, input: &str) -> Result<Vec<ParseError>> {
+        let _span = info_span!("validate", day = S::DAY).entered();
+        S::validate(input)
+    }
+
+    fn implementations(&self) -> &'static [Implementation] {
+        S::IMPLEMENTATIONS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_display() {
+        assert_eq!(Answer::from(-3i64).to_string(), "-3");
+        assert_eq!(Answer::from(42usize).to_string(), "42");
+        assert_eq!(Answer::from("ABC").to_string(), "ABC");
+    }
+
+    #[test]
+    fn implementations() -> Result<()> {
+        let solver = &crate::calendar::tests::Numbers::<1>;
+        assert_eq!(
+            solver.implementation_names(1),
+            ["default", "reversed", "wrong"]
+        );
+        assert_eq!(solver.implementation_names(2), ["default"]);
+        assert_eq!(
+            solver.solve_with("default", "1\n2", 1)?,
+            Answer::Unsigned(3)
+        );
+        assert_eq!(
+            solver.solve_with("reversed", "1\n2", 1)?,
+            Answer::Unsigned(3)
+        );
+        assert!(solver.solve_with("reversed", "1\n2", 2).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+//! Posts answers to the Advent of Code website.
+//!
+//! Every guess is recorded in `guesses.toml` with the verdict of the site, so
+//! an answer already known to be wrong, or out of the recorded too high and
+//! too low bounds, is refused before sending it.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{fetch::Client, input::default_cache_dir};
+
+/// What the site said about a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Verdict {
+    /// The right answer.
+    Correct,
+    /// Wrong, the answer is too high.
+    TooHigh,
+    /// Wrong, the answer is too low.
+    TooLow,
+    /// Wrong, without a hint.
+    Wrong,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "wrong, too high"),
+            Verdict::TooLow => write!(f, "wrong, too low"),
+            Verdict::Wrong => write!(f, "wrong"),
+        }
+    }
+}
+
+/// The response page of a submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+    /// The answer was checked.
+    Verdict(Verdict),
+    /// An answer was sent too recently, retry after the wait.
+    RateLimited(Duration),
+    /// The part is already solved or not unlocked yet.
+    WrongLevel,
+}
+
+impl Response {
+    /// Reads the verdict out of the response page.
+    pub fn parse(page: &str) -> Result<Response> {
+        let text = page
+            .split_once("<article")
+            .map_or(page, |(_, article)| article);
+        if text.contains("That's the right answer") {
+            Ok(Response::Verdict(Verdict::Correct))
+        } else if text.contains("That's not the right answer") {
+            Ok(Response::Verdict(if text.contains("too high") {
+                Verdict::TooHigh
+            } else if text.contains("too low") {
+                Verdict::TooLow
+            } else {
+                Verdict::Wrong
+            }))
+        } else if text.contains("You gave an answer too recently") {
+            Ok(Response::RateLimited(wait_time(text).unwrap_or_default()))
+        } else if text.contains("You don't seem to be solving the right level") {
+            Ok(Response::WrongLevel)
+        } else {
+            bail!("unexpected response page")
+        }
+    }
+}
+
+/// The `1m 5s` of `You have 1m 5s left to wait`.
+fn wait_time(text: &str) -> Option<Duration> {
+    let (before, _) = text.split_once(" left to wait")?;
+    let (_, wait) = before.rsplit_once("have ")?;
+    wait.split_whitespace()
+        .map(|part| {
+            let unit = match part.chars().last()? {
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                _ => return None,
+            };
+            let value = part[..part.len() - 1].parse::<u64>().ok()?;
+            Some(value * unit)
+        })
+        .sum::<Option<u64>>()
+        .map(Duration::from_secs)
+}
+
+/// A submitted answer and its verdict.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Guess {
+    /// Year of the puzzle.
+    pub year: u16,
+    /// Day of the puzzle.
+    pub day: u8,
+    /// Part of the puzzle.
+    pub part: u8,
+    /// The submitted answer.
+    pub answer: String,
+    /// What the site said.
+    pub verdict: Verdict,
+}
+
+/// Every recorded guess.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Guesses {
+    #[serde(default, rename = "guess")]
+    guesses: Vec<Guess>,
+}
+
+impl Guesses {
+    /// Parses the TOML content of a guesses file.
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Loads a guesses file, a missing file has no guesses.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                Self::parse(&content).with_context(|| format!("parsing {}", path.display()))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+        }
+    }
+
+    /// Writes the guesses back as TOML.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)
+            .with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// The guesses of a part, in submission order.
+    pub fn of(&self, year: u16, day: u8, part: u8) -> impl Iterator<Item = &Guess> {
+        self.guesses
+            .iter()
+            .filter(move |g| g.year == year && g.day == day && g.part == part)
+    }
+
+    /// Records the verdict of an answer.
+    pub fn record(&mut self, year: u16, day: u8, part: u8, answer: &str, verdict: Verdict) {
+        self.guesses.push(Guess {
+            year,
+            day,
+            part,
+            answer: answer.to_owned(),
+            verdict,
+        });
+    }
+
+    /// Fails when the guesses already tell the answer is not worth sending.
+    pub fn check(&self, year: u16, day: u8, part: u8, answer: &str) -> Result<()> {
+        let value = answer.parse::<i128>().ok();
+        for guess in self.of(year, day, part) {
+            if guess.answer == answer {
+                bail!("{answer} was already submitted: {}", guess.verdict);
+            }
+            if guess.verdict == Verdict::Correct {
+                bail!("day {day} part {part} is already solved: {}", guess.answer);
+            }
+            let (Some(value), Ok(bound)) = (value, guess.answer.parse::<i128>()) else {
+                continue;
+            };
+            match guess.verdict {
+                Verdict::TooHigh if value >= bound => {
+                    bail!("{answer} is too high, {bound} already was")
+                }
+                Verdict::TooLow if value <= bound => {
+                    bail!("{answer} is too low, {bound} already was")
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `<user cache>/advent-of-code/<year>/guesses.toml`.
+pub fn default_path(year: u16) -> Option<PathBuf> {
+    default_cache_dir(year).map(|dir| dir.join("guesses.toml"))
+}
+
+/// Checks the answer against the guesses, posts it and records the verdict.
+pub fn submit(
+    client: &Client,
+    guesses: &mut Guesses,
+    day: u8,
+    part: u8,
+    answer: &str,
+) -> Result<Response> {
+    let year = client.year();
+    guesses.check(year, day, part, answer)?;
+    let page = client
+        .post(
+            &format!("/{year}/day/{day}/answer"),
+            &[("level", &part.to_string()), ("answer", answer)],
+        )
+        .with_context(|| format!("submitting day {day} part {part}"))?;
+    let response = Response::parse(&page)?;
+    if let Response::Verdict(verdict) = response {
+        guesses.record(year, day, part, answer, verdict);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::mock_server;
+
+    const TOO_HIGH: &str = "<main><article><p>That's not the right answer; \
+        your answer is too high. If you're stuck, make sure you're using the \
+        full input data.</p></article></main>";
+    const CORRECT: &str = "<main><article><p>That's the right answer! You are \
+        one gold star closer to restoring snow operations.</p></article></main>";
+
+    #[test]
+    fn parse_response() -> Result<()> {
+        assert_eq!(
+            Response::parse(TOO_HIGH)?,
+            Response::Verdict(Verdict::TooHigh)
+        );
+        assert_eq!(
+            Response::parse(CORRECT)?,
+            Response::Verdict(Verdict::Correct)
+        );
+        assert_eq!(
+            Response::parse(
+                "<article><p>That's not the right answer; your answer is too low.</p></article>"
+            )?,
+            Response::Verdict(Verdict::TooLow)
+        );
+        assert_eq!(
+            Response::parse("<article><p>That's not the right answer.</p></article>")?,
+            Response::Verdict(Verdict::Wrong)
+        );
+        assert_eq!(
+            Response::parse(
+                "<article><p>You gave an answer too recently; you have to wait after \
+                 submitting an answer before trying again.  You have 1m 5s left to wait.</p></article>"
+            )?,
+            Response::RateLimited(Duration::from_secs(65))
+        );
+        assert_eq!(
+            Response::parse(
+                "<article><p>You don't seem to be solving the right level.</p></article>"
+            )?,
+            Response::WrongLevel
+        );
+        assert!(Response::parse("<html></html>").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn check_guesses() {
+        let mut guesses = Guesses::default();
+        guesses.record(2023, 10, 2, "500", Verdict::TooHigh);
+        guesses.record(2023, 10, 2, "100", Verdict::TooLow);
+        guesses.record(2023, 10, 2, "337x", Verdict::Wrong);
+
+        assert!(guesses.check(2023, 10, 2, "500").is_err());
+        assert!(guesses.check(2023, 10, 2, "600").is_err());
+        assert!(guesses.check(2023, 10, 2, "99").is_err());
+        assert!(guesses.check(2023, 10, 2, "337x").is_err());
+        assert!(guesses.check(2023, 10, 2, "337").is_ok());
+        assert!(guesses.check(2023, 10, 1, "600").is_ok());
+        assert!(guesses.check(2022, 10, 2, "500").is_ok());
+
+        guesses.record(2023, 10, 2, "337", Verdict::Correct);
+        assert!(guesses.check(2023, 10, 2, "338").is_err());
+    }
+
+    #[test]
+    fn submit_and_record() -> Result<()> {
+        let (url, server) = mock_server(vec![(200, TOO_HIGH), (200, CORRECT)]);
+        let client = Client::new(2023)
+            .base_url(url)
+            .session(Some("abc123".to_owned()))
+            .interval(Duration::ZERO);
+        let mut guesses = Guesses::default();
+
+        let response = submit(&client, &mut guesses, 10, 2, "400")?;
+        assert_eq!(response, Response::Verdict(Verdict::TooHigh));
+        // refused locally, the server never sees it
+        assert!(submit(&client, &mut guesses, 10, 2, "401").is_err());
+        let response = submit(&client, &mut guesses, 10, 2, "337")?;
+        assert_eq!(response, Response::Verdict(Verdict::Correct));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("POST /2023/day/10/answer HTTP/1.1"));
+        assert!(requests[0].ends_with("level=2&answer=400"));
+        let reparsed = Guesses::parse(&toml::to_string(&guesses)?)?;
+        assert_eq!(reparsed.of(2023, 10, 2).count(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,534 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{bail, Context, Result};
+use aoc_2023::gen::{self, Knobs};
+use aoc_core::{
+    answers::{self, AnswerStore, Status},
+    fetch::Client,
+    normalise::Mode,
+    report, scaffold,
+    solution::DEFAULT_IMPLEMENTATION,
+    submit::{self, Guesses, Response},
+    Calendar,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Advent of Code solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Year of the puzzles, the latest registered one by default, or every
+    /// year for `run --all` and `verify`
+    #[arg(short, long, global = true)]
+    year: Option<u16>,
+    /// Print the spans and events of the solvers on stderr, filtered by
+    /// `RUST_LOG` when it is set
+    #[arg(long, global = true)]
+    trace: bool,
+    /// Format of the traces
+    #[arg(long, global = true, value_enum, default_value_t = TraceFormat::Text)]
+    trace_format: TraceFormat,
+    /// Reject inputs with a byte order mark, CRLF, trailing whitespace, an
+    /// indented first line or trailing blank lines instead of repairing them
+    #[arg(long, global = true)]
+    strict: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TraceFormat {
+    /// One line of text per event
+    Text,
+    /// One JSON object per event
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the solver for a day and part, or every day with `--all`
+    Run {
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25), required_unless_present = "all")]
+        day: Option<u8>,
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2), required_unless_present = "all")]
+        part: Option<u8>,
+        /// Puzzle input, otherwise looked up in `AOC_INPUT_DIR`, the cache,
+        /// the bundled inputs, downloaded with `AOC_SESSION` or read from
+        /// stdin
+        #[arg(short, long, conflicts_with = "all")]
+        input: Option<PathBuf>,
+        /// Run both parts of every registered day and compare with the
+        /// stored answers
+        #[arg(short, long, conflicts_with_all = ["day", "part"])]
+        all: bool,
+        /// Run the days on all cores
+        #[arg(long, requires = "all")]
+        parallel: bool,
+        /// Print the report as JSON
+        #[arg(long, requires = "all")]
+        json: bool,
+        /// Implementation of the part to run, `aoc compare` lists them
+        #[arg(long = "impl", conflicts_with = "all")]
+        implementation: Option<String>,
+    },
+    /// Run every implementation of a day on the same input, timing them and
+    /// reporting the answers that disagree with the default one
+    Compare {
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Only this part, both by default
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+        /// Puzzle input, looked up like for `run`
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Post an answer, by default the one the solver gives
+    Submit {
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+        /// Answer to post instead of running the solver
+        #[arg(short, long)]
+        answer: Option<String>,
+        /// Puzzle input, looked up like for `run`
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Check that the input of a day satisfies what its solvers assume
+    Check {
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Puzzle input, looked up like for `run`
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// List the registered days
+    List,
+    /// Generate the module, input placeholder and tests of a new day
+    New {
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+    },
+    /// Check every registered day against the stored answers of every input
+    Verify {
+        /// Answers file, `answers.toml` of the workspace by default
+        #[arg(short, long)]
+        answers: Option<PathBuf>,
+        /// Store the answers of inputs that have none yet
+        #[arg(long)]
+        record: bool,
+    },
+    /// Print a generated input, sized like the real one unless a knob is given
+    Gen {
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Seed of the generator, the same seed gives the same input
+        #[arg(short, long, default_value_t = 0)]
+        seed: u64,
+        /// Lines of the inputs that are lists, or galaxies of day 11
+        #[arg(long)]
+        lines: Option<usize>,
+        /// Columns of the maps, values of a day 9 history or day 8 directions
+        #[arg(long)]
+        width: Option<usize>,
+        /// Rows of the maps
+        #[arg(long)]
+        height: Option<usize>,
+        /// Ranges in each day 5 map
+        #[arg(long)]
+        ranges: Option<usize>,
+        /// Springs in each day 12 row
+        #[arg(long)]
+        springs: Option<usize>,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if cli.trace {
+        init_tracing(cli.trace_format);
+    }
+    let year = cli.year;
+    let mode = if cli.strict {
+        Mode::Strict
+    } else {
+        Mode::Repair
+    };
+    match cli.command {
+        Command::Run {
+            all: true,
+            parallel,
+            json,
+            ..
+        } => run_all(calendars(year)?, mode, parallel, json)?,
+        Command::Run {
+            day,
+            part,
+            input,
+            implementation,
+            ..
+        } => {
+            let (Some(day), Some(part)) = (day, part) else {
+                bail!("--day and --part are required without --all");
+            };
+            let calendar = calendar(year)?;
+            let year = calendar.year;
+            let solver = calendar
+                .get(day)
+                .with_context(|| format!("day {day} of {year} is not solved yet"))?;
+            let provider = calendar
+                .inputs()
+                .path(input)
+                .fetch(Some(Client::new(year)))
+                .normalise(mode);
+            let start = Instant::now();
+            // only the default implementations stream their input
+            let answer = match implementation {
+                Some(name) => solver.solve_with(&name, &provider.load(day)?, part)?,
+                None => solver.solve_reader(&mut provider.open(day)?, part)?,
+            };
+            let elapsed = start.elapsed();
+            println!("{year} day {day} part {part}: {answer}");
+            println!("elapsed: {elapsed:?}");
+        }
+        Command::Check { day, input } => check(calendar(year)?, mode, day, input)?,
+        Command::Compare { day, part, input } => compare(calendar(year)?, mode, day, part, input)?,
+        Command::List => {
+            for calendar in calendars(year)? {
+                for solver in calendar.days {
+                    println!("{} day {}", calendar.year, solver.day());
+                }
+            }
+        }
+        Command::New { day } => {
+            let year = calendar(year)?.year;
+            // every year crate is a directory named after its year
+            let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join(year.to_string());
+            for path in scaffold::scaffold(&root, day)? {
+                println!("wrote {}", path.display());
+            }
+        }
+        Command::Submit {
+            day,
+            part,
+            answer,
+            input,
+        } => submit(calendar(year)?, mode, day, part, answer, input)?,
+        Command::Verify { answers, record } => verify(calendars(year)?, mode, answers, record)?,
+        Command::Gen {
+            day,
+            seed,
+            lines,
+            width,
+            height,
+            ranges,
+            springs,
+        } => {
+            if calendar(year)?.year != aoc_2023::YEAR {
+                bail!("only the inputs of {} can be generated", aoc_2023::YEAR);
+            }
+            let real = Knobs::of(day);
+            let knobs = Knobs {
+                lines: lines.unwrap_or(real.lines),
+                width: width.unwrap_or(real.width),
+                height: height.unwrap_or(real.height),
+                ranges: ranges.unwrap_or(real.ranges),
+                springs: springs.unwrap_or(real.springs),
+            };
+            print!("{}", gen::generate(day, seed, &knobs)?);
+        }
+    }
+    Ok(())
+}
+
+/// Every registered year, in order.
+static YEARS: &[&Calendar] = &[&aoc_2023::CALENDAR];
+
+/// The calendar of `year`, the latest one by default.
+fn calendar(year: Option<u16>) -> Result<&'static Calendar> {
+    match year {
+        Some(year) => YEARS
+            .iter()
+            .copied()
+            .find(|calendar| calendar.year == year)
+            .with_context(|| format!("no solutions for {year}")),
+        None => Ok(YEARS.last().expect("a year is registered")),
+    }
+}
+
+/// The calendar of `year`, or every calendar.
+fn calendars(year: Option<u16>) -> Result<Vec<&'static Calendar>> {
+    match year {
+        Some(_) => Ok(vec![calendar(year)?]),
+        None => Ok(YEARS.to_vec()),
+    }
+}
+
+/// Sends the solver traces to stderr, at debug level unless `RUST_LOG` says
+/// otherwise.
+fn init_tracing(format: TraceFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        let crates = YEARS.iter().map(|calendar| calendar.module);
+        EnvFilter::new(
+            crates
+                .chain(["aoc_core"])
+                .map(|module| format!("{module}=debug"))
+                .collect::<Vec<String>>()
+                .join(","),
+        )
+    });
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(io::stderr);
+    match format {
+        TraceFormat::Text => subscriber.init(),
+        TraceFormat::Json => subscriber.json().init(),
+    }
+}
+
+fn check(calendar: &Calendar, mode: Mode, day: u8, input: Option<PathBuf>) -> Result<()> {
+    let year = calendar.year;
+    let solver = calendar
+        .get(day)
+        .with_context(|| format!("day {day} of {year} is not solved yet"))?;
+    let input = calendar
+        .inputs()
+        .path(input)
+        .fetch(Some(Client::new(year)))
+        .normalise(mode)
+        .load(day)?;
+    let violations = solver.validate(&input)?;
+    for violation in &violations {
+        println!("{violation}");
+    }
+    match violations.len() {
+        0 => println!("{year} day {day}: no violation"),
+        1 => bail!("1 violation in the input of {year} day {day}"),
+        n => bail!("{n} violations in the input of {year} day {day}"),
+    }
+    Ok(())
+}
+
+fn compare(
+    calendar: &Calendar,
+    mode: Mode,
+    day: u8,
+    part: Option<u8>,
+    input: Option<PathBuf>,
+) -> Result<()> {
+    let year = calendar.year;
+    let solver = calendar
+        .get(day)
+        .with_context(|| format!("day {day} of {year} is not solved yet"))?;
+    let input = calendar
+        .inputs()
+        .path(input)
+        .fetch(Some(Client::new(year)))
+        .normalise(mode)
+        .load(day)?;
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+    println!(
+        "{:>4}  {:<16} {:<16} {:>12}  status",
+        "part", "implementation", "answer", "time"
+    );
+    let mut disagreements = 0;
+    for part in parts {
+        for comparison in report::compare(solver, &input, part) {
+            let (answer, status) = match &comparison.answer {
+                Ok(answer) if comparison.agrees => (answer.as_str(), "ok".to_owned()),
+                Ok(answer) => (answer.as_str(), "DISAGREES".to_owned()),
+                Err(e) => ("-", format!("FAILED: {e}")),
+            };
+            if !comparison.agrees {
+                disagreements += 1;
+            }
+            println!(
+                "{:>4}  {:<16} {:<16} {:>12}  {status}",
+                comparison.part,
+                comparison.implementation,
+                answer,
+                format!("{:.2?}", comparison.time),
+            );
+        }
+    }
+    if disagreements > 0 {
+        bail!("{disagreements} implementations of {year} day {day} disagree or failed");
+    }
+    Ok(())
+}
+
+fn submit(
+    calendar: &Calendar,
+    mode: Mode,
+    day: u8,
+    part: u8,
+    answer: Option<String>,
+    input: Option<PathBuf>,
+) -> Result<()> {
+    let year = calendar.year;
+    let client = Client::new(year);
+    let input = calendar
+        .inputs()
+        .path(input)
+        .fetch(Some(client.clone()))
+        .normalise(mode)
+        .load(day)?;
+    let answer = match answer {
+        Some(answer) => answer,
+        None => calendar
+            .get(day)
+            .with_context(|| format!("day {day} of {year} is not solved yet"))?
+            .solve(&input, part)?
+            .to_string(),
+    };
+    let path = submit::default_path(year).context("no cache directory for the guesses")?;
+    let mut guesses = Guesses::load(&path)?;
+    let response = submit::submit(&client, &mut guesses, day, part, &answer)?;
+    guesses.save(&path)?;
+    match response {
+        Response::Verdict(verdict) => {
+            println!("{year} day {day} part {part}: {answer} is {verdict}");
+            if verdict == submit::Verdict::Correct {
+                let answers_path = answers::default_path();
+                let mut store = AnswerStore::load(&answers_path)?;
+                let hash = answers::input_hash(&input);
+                store.record(year, day, &hash, part, &answer.into());
+                store.save(&answers_path)?;
+            }
+        }
+        Response::RateLimited(wait) => bail!("answered too recently, wait {wait:?}"),
+        Response::WrongLevel => bail!("day {day} part {part} is solved or locked"),
+    }
+    Ok(())
+}
+
+fn run_all(calendars: Vec<&Calendar>, mode: Mode, parallel: bool, json: bool) -> Result<()> {
+    let store = AnswerStore::load(&answers::default_path())?;
+    let reports = calendars
+        .into_iter()
+        .map(|calendar| {
+            let provider = calendar.inputs().stdin(false).normalise(mode);
+            report::run_all(&store, calendar, &provider, parallel)
+        })
+        .collect::<Vec<report::Report>>();
+    // one report of every year, timed as a whole
+    let report = report::Report {
+        total: reports.iter().map(|report| report.total).sum(),
+        runs: reports.into_iter().flat_map(|report| report.runs).collect(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "{:>4} {:>3} {:>4}  {:<16} {:>12} {:>12}  status",
+            "year", "day", "part", "answer", "parse", "solve"
+        );
+        for run in &report.runs {
+            let status = match &run.status {
+                Status::Ok => "ok".to_owned(),
+                Status::Mismatch => format!(
+                    "MISMATCH, expected {}",
+                    run.expected.as_deref().unwrap_or("-")
+                ),
+                Status::Unknown => "unknown".to_owned(),
+                Status::Failed(e) => format!("FAILED: {e}"),
+            };
+            println!(
+                "{:>4} {:>3} {:>4}  {:<16} {:>12} {:>12}  {status}",
+                run.year,
+                run.day,
+                run.part,
+                run.answer.as_deref().unwrap_or("-"),
+                format!("{:.2?}", run.parse),
+                format!("{:.2?}", run.solve),
+            );
+        }
+        println!("total: {:.2?}", report.total);
+    }
+    let failures = report.failures();
+    if failures > 0 {
+        bail!("{failures} of {} runs failed", report.runs.len());
+    }
+    Ok(())
+}
+
+fn verify(
+    calendars: Vec<&Calendar>,
+    mode: Mode,
+    path: Option<PathBuf>,
+    record: bool,
+) -> Result<()> {
+    let path = path.unwrap_or_else(answers::default_path);
+    let mut store = AnswerStore::load(&path)?;
+    let checks = calendars
+        .into_iter()
+        .flat_map(|calendar| {
+            let provider = calendar.inputs().stdin(false).normalise(mode);
+            answers::verify(&store, calendar, &provider)
+        })
+        .collect::<Vec<answers::Check>>();
+
+    println!(
+        "{:>4} {:>3} {:>4}  {:<12} {:<12} {:<16} {:<16} {:<10} source",
+        "year", "day", "part", "impl", "input", "expected", "actual", "status"
+    );
+    let mut failures = 0;
+    for check in &checks {
+        let status = match &check.status {
+            Status::Ok => "ok".to_owned(),
+            Status::Mismatch => "MISMATCH".to_owned(),
+            Status::Unknown => "unknown".to_owned(),
+            Status::Failed(e) => format!("FAILED: {e}"),
+        };
+        if matches!(check.status, Status::Mismatch | Status::Failed(_)) {
+            failures += 1;
+        }
+        println!(
+            "{:>4} {:>3} {:>4}  {:<12} {:<12} {:<16} {:<16} {status:<10} {}",
+            check.year,
+            check.day,
+            check.part,
+            check.implementation,
+            check.input.get(..12).unwrap_or("-"),
+            check.expected.as_deref().unwrap_or("-"),
+            check.actual.as_deref().unwrap_or("-"),
+            check.source,
+        );
+        // the other implementations are checked against the default one
+        if record
+            && check.status == Status::Unknown
+            && check.implementation == DEFAULT_IMPLEMENTATION
+        {
+            if let Some(actual) = &check.actual {
+                store.record(
+                    check.year,
+                    check.day,
+                    &check.input,
+                    check.part,
+                    &actual.as_str().into(),
+                );
+            }
+        }
+    }
+    if record {
+        store.save(&path)?;
+    }
+    if failures > 0 {
+        bail!("{failures} of {} checks failed", checks.len());
+    }
+    println!("{} checks passed", checks.len());
+    Ok(())
+}